@@ -0,0 +1,11 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use rustysynth_midi::MidiFile;
+
+// Any input must be either loaded or rejected with an error, without panicking.
+fuzz_target!(|data: &[u8]| {
+    if let Ok(midi_file) = MidiFile::new(&mut &data[..]) {
+        let _ = midi_file.get_length();
+    }
+});
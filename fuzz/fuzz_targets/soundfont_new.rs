@@ -0,0 +1,9 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use rustysynth_soundfont::SoundFont;
+
+// Any input must be either loaded or rejected with an error, without panicking.
+fuzz_target!(|data: &[u8]| {
+    let _ = SoundFont::new(&mut &data[..]);
+});
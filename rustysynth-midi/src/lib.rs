@@ -9,6 +9,11 @@ use rustysynth::{SoundSource, Synthesizer};
 
 pub trait MidiAdapter {
     fn process_midi_message(&mut self, channel: u4, msg: MidiMessage);
+
+    /// Processes a system exclusive message.
+    /// The data may or may not include the leading 0xF0 and the trailing 0xF7.
+    /// Unsupported messages are ignored.
+    fn process_sysex(&mut self, _data: &[u8]) {}
 }
 
 impl<Source: SoundSource> MidiAdapter for Synthesizer<Source> {
@@ -24,16 +29,28 @@ impl<Source: SoundSource> MidiAdapter for Synthesizer<Source> {
             MidiMessage::Controller { controller, value } => match controller.as_int() {
                 0x00 => self.set_bank(channel, value.as_int()),
                 0x01 => self.set_modulation_coarse(channel, value.as_int()),
+                0x05 => self.set_portamento_time_coarse(channel, value.as_int()),
                 0x06 => self.data_entry_coarse(channel, value.as_int()),
                 0x07 => self.set_volume_coarse(channel, value.as_int()),
                 0x0A => self.set_pan_coarse(channel, value.as_int()),
                 0x0B => self.set_expression_coarse(channel, value.as_int()),
+                0x20 => self.set_bank_lsb(channel, value.as_int()),
                 0x21 => self.set_modulation_fine(channel, value.as_int()),
+                0x25 => self.set_portamento_time_fine(channel, value.as_int()),
                 0x26 => self.data_entry_fine(channel, value.as_int()),
                 0x27 => self.set_volume_fine(channel, value.as_int()),
                 0x2A => self.set_pan_fine(channel, value.as_int()),
                 0x2B => self.set_expression_fine(channel, value.as_int()),
                 0x40 => self.set_hold_pedal(channel, value.as_int()),
+                0x41 => self.set_portamento(channel, value.as_int()),
+                0x42 => self.set_sostenuto_pedal(channel, value.as_int()),
+                0x43 => self.set_soft_pedal(channel, value.as_int()),
+                0x47 => self.set_harmonic_content(channel, value.as_int()),
+                0x48 => self.set_release_time(channel, value.as_int()),
+                0x49 => self.set_attack_time(channel, value.as_int()),
+                0x4A => self.set_brightness(channel, value.as_int()),
+                0x4B => self.set_decay_time(channel, value.as_int()),
+                0x54 => self.set_portamento_control(channel, value.as_int()),
                 0x5B => self.set_reverb_send(channel, value.as_int()),
                 0x5D => self.set_chorus_send(channel, value.as_int()),
                 0x63 => self.set_nrpn_coarse(channel, value.as_int()),
@@ -43,11 +60,66 @@ impl<Source: SoundSource> MidiAdapter for Synthesizer<Source> {
                 0x78 => self.note_off_all_channel(channel.into(), true),
                 0x79 => self.reset_all_controllers_channel(channel.into()),
                 0x7B => self.note_off_all_channel(channel.into(), false),
+                0x7E => self.set_mono_mode(channel, true),
+                0x7F => self.set_mono_mode(channel, false),
                 _ => (),
             },
             MidiMessage::ProgramChange { program } => self.set_patch(channel, program.as_int()),
             MidiMessage::PitchBend { bend } => self.set_pitch_bend(channel, bend.0.as_int()),
+            MidiMessage::ChannelAftertouch { vel } => {
+                self.set_channel_pressure(channel, vel.as_int())
+            }
+            MidiMessage::Aftertouch { key, vel } => {
+                self.set_poly_pressure(channel, key.as_int(), vel.as_int())
+            }
+        }
+    }
+
+    fn process_sysex(&mut self, data: &[u8]) {
+        let data = data.strip_prefix(&[0xF0]).unwrap_or(data);
+        let data = data.strip_suffix(&[0xF7]).unwrap_or(data);
+
+        // The messages for any device ID are accepted.
+        match *data {
+            // Universal Real-Time: Master Volume.
+            [0x7F, _, 0x04, 0x01, lsb, msb] => {
+                self.set_midi_master_volume(to_14_bit(lsb, msb));
+            }
+            // Universal Real-Time: Master Balance.
+            [0x7F, _, 0x04, 0x02, lsb, msb] => {
+                self.set_midi_master_balance(to_14_bit(lsb, msb));
+            }
+            // GS: Use for Rhythm Part, where 0 is off and 1 or 2 selects a drum map.
+            // A part which the synthesizer does not have is ignored.
+            [0x41, _, 0x42, 0x12, 0x40, address, 0x15, value, checksum]
+                if address & 0xF0 == 0x10
+                    && gs_checksum(&[0x40, address, 0x15, value]) == checksum =>
+            {
+                let channel = gs_part_to_channel(address & 0x0F);
+                if (channel as usize) < self.get_channel_count() {
+                    self.set_percussion(channel, value != 0);
+                }
+            }
             _ => (),
         }
     }
 }
+
+fn to_14_bit(lsb: u8, msb: u8) -> u16 {
+    ((msb as u16 & 0x7F) << 7) | (lsb as u16 & 0x7F)
+}
+
+// The Roland checksum, which makes the sum of the address, the data and itself a multiple of 128.
+fn gs_checksum(data: &[u8]) -> u8 {
+    let sum = data.iter().map(|&value| value as u32).sum::<u32>();
+    ((128 - sum % 128) % 128) as u8
+}
+
+// GS numbers the parts from 1 to 16 in the order of 10, 1 to 9 and 11 to 16.
+fn gs_part_to_channel(part: u8) -> u8 {
+    match part {
+        0 => 9,
+        1..=9 => part - 1,
+        _ => part,
+    }
+}
@@ -8,8 +8,17 @@ pub struct MidiFile {
 #[derive(Debug)]
 pub struct MidiEvent {
     pub(crate) time: f64,
-    pub(crate) ch: midly::num::u4,
-    pub(crate) msg: midly::MidiMessage,
+    pub(crate) kind: MidiEventKind,
+}
+
+#[derive(Debug)]
+pub(crate) enum MidiEventKind {
+    Channel {
+        ch: midly::num::u4,
+        msg: midly::MidiMessage,
+    },
+    /// The data of a system exclusive message without the leading 0xF0.
+    SysEx(Box<[u8]>),
 }
 
 #[derive(Debug)]
@@ -22,9 +31,13 @@ impl MidiFile {
     pub fn new<R: Read>(reader: &mut R) -> Result<Self> {
         let mut buf = vec![];
         reader.read_to_end(&mut buf)?;
+        if has_invalid_timecode(&buf) {
+            return Err(anyhow!("Timecode is not supported"));
+        }
         let smf = midly::Smf::parse(buf.as_slice())?;
         let ticks_per_beat = match smf.header.timing {
-            midly::Timing::Metrical(tpb) => tpb.as_int() as f64,
+            midly::Timing::Metrical(tpb) if tpb.as_int() > 0 => tpb.as_int() as f64,
+            midly::Timing::Metrical(..) => return Err(anyhow!("The resolution must not be zero")),
             midly::Timing::Timecode(..) => return Err(anyhow!("Timecode is not supported")),
         };
         // The first track contains all of the tempo changes. These apply to the
@@ -52,18 +65,29 @@ impl MidiFile {
                 let delta_us = delta_beats * us_per_beat; // B * us/B = us
                 let delta_s = delta_us / 1_000_000.0; // us / 1_000_000 = s
                 time += delta_s;
-                if first_track {
-                    if let midly::TrackEventKind::Meta(midly::MetaMessage::Tempo(tempo)) = kind {
-                        us_per_beat = tempo.as_int() as f64;
-                        tempo_changes.push(TempoChange { time, us_per_beat });
-                    }
+                if first_track
+                    && let midly::TrackEventKind::Meta(midly::MetaMessage::Tempo(tempo)) = kind
+                {
+                    us_per_beat = tempo.as_int() as f64;
+                    tempo_changes.push(TempoChange { time, us_per_beat });
                 }
-                if let midly::TrackEventKind::Midi { channel, message } = kind {
-                    track_evts.push_back(MidiEvent {
-                        time,
-                        ch: channel,
-                        msg: message,
-                    });
+                match kind {
+                    midly::TrackEventKind::Midi { channel, message } => {
+                        track_evts.push_back(MidiEvent {
+                            time,
+                            kind: MidiEventKind::Channel {
+                                ch: channel,
+                                msg: message,
+                            },
+                        });
+                    }
+                    midly::TrackEventKind::SysEx(data) => {
+                        track_evts.push_back(MidiEvent {
+                            time,
+                            kind: MidiEventKind::SysEx(data.into()),
+                        });
+                    }
+                    _ => (),
                 }
             }
             all_evts.push(track_evts);
@@ -76,11 +100,11 @@ impl MidiFile {
                 .iter()
                 .enumerate()
                 .filter_map(|(i, evts)| evts.front().map(|evt| (i, evt.time)))
-                .min_by(|a, b| a.1.partial_cmp(&b.1).unwrap())
-                .map(|(i, _)| i)
-                .unwrap();
-            let which = &mut all_evts[which];
-            let evt = which.pop_front().unwrap();
+                .min_by(|a, b| a.1.total_cmp(&b.1))
+                .map(|(i, _)| i);
+            let Some(evt) = which.and_then(|i| all_evts[i].pop_front()) else {
+                break;
+            };
             events.push(evt);
         }
 
@@ -88,7 +112,49 @@ impl MidiFile {
     }
 
     /// Get the length of the MIDI file in seconds.
+    /// The length of a file without events is zero.
     pub fn get_length(&self) -> f64 {
-        self.events.last().unwrap().time
+        self.events.last().map_or(0.0, |evt| evt.time)
+    }
+}
+
+// midly overflows when it negates the SMPTE format -128, which is not a valid frame rate.
+// Such a header is rejected before parsing, as the timecode is not supported anyway.
+fn has_invalid_timecode(raw: &[u8]) -> bool {
+    let raw = if raw.starts_with(b"RIFF") {
+        // The SMF of an RMID file is stored in the 'data' chunk.
+        match find_rmid_data(raw) {
+            Some(data) => data,
+            None => return false,
+        }
+    } else {
+        raw
+    };
+
+    if !raw.starts_with(b"MThd") || raw.len() < 14 {
+        return false;
     }
+    let length = u32::from_be_bytes([raw[4], raw[5], raw[6], raw[7]]) as usize;
+    length >= 6 && raw[12] == 0x80
+}
+
+fn find_rmid_data(raw: &[u8]) -> Option<&[u8]> {
+    let mut chunks = get_riff_chunk(raw)?.1.get(4..)?;
+    while let Some((id, data)) = get_riff_chunk(chunks) {
+        if id == b"data" {
+            return Some(data);
+        }
+        let next = 8 + data.len() + data.len() % 2;
+        chunks = chunks.get(next..)?;
+    }
+    None
+}
+
+// Splits the chunk at the start into the ID and the data.
+// The data is cut at the end of the input, as midly does.
+fn get_riff_chunk(raw: &[u8]) -> Option<(&[u8], &[u8])> {
+    let header = raw.get(..8)?;
+    let length = u32::from_le_bytes([header[4], header[5], header[6], header[7]]) as usize;
+    let data = &raw[8..];
+    Some((&header[..4], &data[..length.min(data.len())]))
 }
@@ -1,4 +1,4 @@
-use crate::midifile::{MidiEvent, MidiFile};
+use crate::midifile::{MidiEventKind, MidiFile};
 use crate::MidiAdapter;
 use rustysynth::{SoundSource, Synthesizer};
 
@@ -33,9 +33,14 @@ impl<Source: SoundSource> MidiFileSequencer<Source> {
 
     fn process_events(&mut self) {
         while self.msg_index < self.midi_file.events.len() {
-            let MidiEvent { time, ch, msg } = self.midi_file.events[self.msg_index];
-            if time <= self.current_time {
-                self.synthesizer.process_midi_message(ch, msg);
+            let event = &self.midi_file.events[self.msg_index];
+            if event.time <= self.current_time {
+                match &event.kind {
+                    MidiEventKind::Channel { ch, msg } => {
+                        self.synthesizer.process_midi_message(*ch, *msg)
+                    }
+                    MidiEventKind::SysEx(data) => self.synthesizer.process_sysex(data),
+                }
                 self.msg_index += 1;
             } else {
                 break;
@@ -0,0 +1,74 @@
+//! Extracts the samples of a SoundFont to WAV files.
+//!
+//! Usage: extract_samples <soundfont> <output directory> [bank:program...]
+//!
+//! If presets are given, only the samples used by them are extracted.
+
+use rustysynth::PresetId;
+use rustysynth_soundfont::{SampleExporter, SoundFont};
+use std::env;
+use std::fs;
+use std::fs::File;
+use std::io::BufReader;
+use std::process::ExitCode;
+
+fn main() -> ExitCode {
+    let args: Vec<String> = env::args().collect();
+    if args.len() < 3 {
+        eprintln!(
+            "Usage: {} <soundfont> <output directory> [bank:program...]",
+            args[0]
+        );
+        return ExitCode::FAILURE;
+    }
+
+    let mut preset_ids: Vec<PresetId> = Vec::new();
+    for arg in &args[3..] {
+        match parse_preset_id(arg) {
+            Some(preset_id) => preset_ids.push(preset_id),
+            None => {
+                eprintln!("Invalid preset '{}', expected bank:program", arg);
+                return ExitCode::FAILURE;
+            }
+        }
+    }
+
+    let sound_font = match File::open(&args[1])
+        .map_err(|err| err.to_string())
+        .and_then(|file| SoundFont::new(&mut BufReader::new(file)).map_err(|err| err.to_string()))
+    {
+        Ok(sound_font) => sound_font,
+        Err(err) => {
+            eprintln!("Failed to load '{}': {}", args[1], err);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let mut exporter = SampleExporter::new(&sound_font);
+    if !preset_ids.is_empty() {
+        exporter.select_presets(&preset_ids);
+    }
+
+    let result = fs::create_dir_all(&args[2]).and_then(|_| exporter.write_to_directory(&args[2]));
+    match result {
+        Ok(paths) => {
+            for path in paths {
+                println!("{}", path.display());
+            }
+            ExitCode::SUCCESS
+        }
+        Err(err) => {
+            eprintln!("Failed to write the samples: {}", err);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn parse_preset_id(value: &str) -> Option<PresetId> {
+    let (bank, program) = value.split_once(':')?;
+    let program: u8 = program.parse().ok()?;
+    if program >= 128 {
+        return None;
+    }
+    Some(PresetId::new(bank.parse().ok()?, program))
+}
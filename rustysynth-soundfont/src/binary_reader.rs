@@ -1,7 +1,5 @@
 use std::io;
 use std::io::Read;
-use std::slice;
-use std::str;
 
 use crate::four_cc::FourCC;
 
@@ -54,43 +52,56 @@ impl BinaryReader {
         reader: &mut R,
         length: usize,
     ) -> Result<String, io::Error> {
-        let mut data: Vec<u8> = vec![0; length];
-        reader.read_exact(&mut data)?;
-
-        let mut actual_length: usize = 0;
-        for value in &mut data {
-            if *value == 0 {
-                break;
-            }
-            actual_length += 1;
+        // The buffer only grows as the data arrives, so a wrong length cannot cause a huge allocation.
+        let mut data: Vec<u8> = Vec::new();
+        reader.take(length as u64).read_to_end(&mut data)?;
+        if data.len() != length {
+            return Err(io::Error::from(io::ErrorKind::UnexpectedEof));
         }
 
+        let actual_length = data.iter().position(|value| *value == 0).unwrap_or(length);
+
         // Replace non-ASCII characters with '?'.
         // Tabs and returns are preserved.
-        for value in &mut data[0..actual_length] {
-            if !(9..=126).contains(value) {
-                *value = 63; // '?'
-            }
-        }
-
-        Ok(str::from_utf8(&data[0..actual_length]).unwrap().to_string())
+        Ok(data[0..actual_length]
+            .iter()
+            .map(|value| {
+                if (9..=126).contains(value) {
+                    *value as char
+                } else {
+                    '?'
+                }
+            })
+            .collect())
     }
 
     pub(crate) fn discard_data<R: Read>(reader: &mut R, size: usize) -> Result<(), io::Error> {
-        let mut data: Vec<u8> = vec![0; size];
-        reader.read_exact(&mut data)
+        let discarded = io::copy(&mut reader.take(size as u64), &mut io::sink())?;
+        if discarded != size as u64 {
+            return Err(io::Error::from(io::ErrorKind::UnexpectedEof));
+        }
+        Ok(())
     }
 
     pub(crate) fn read_wave_data<R: Read>(
         reader: &mut R,
         size: usize,
     ) -> Result<Vec<i16>, io::Error> {
-        let length = size / 2;
-        let mut samples: Vec<i16> = vec![0; length];
-
-        let ptr = samples.as_mut_ptr() as *mut u8;
-        let data = unsafe { slice::from_raw_parts_mut(ptr, size) };
-        reader.read_exact(data)?;
+        // The data is read block by block, so that the memory is only allocated for the data which actually exists.
+        // An odd byte at the end is not a part of any sample.
+        let mut samples: Vec<i16> = Vec::new();
+        let mut block: [u8; 8192] = [0; 8192];
+        let mut remaining = size;
+        while remaining > 0 {
+            let length = remaining.min(block.len());
+            reader.read_exact(&mut block[0..length])?;
+            samples.extend(
+                block[0..length]
+                    .chunks_exact(2)
+                    .map(|pair| i16::from_le_bytes([pair[0], pair[1]])),
+            );
+            remaining -= length;
+        }
 
         Ok(samples)
     }
@@ -2,6 +2,8 @@ use std::error;
 use std::fmt;
 use std::io;
 
+use rustysynth::PresetId;
+
 use crate::four_cc::FourCC;
 
 /// Represents an error when loading a SoundFont.
@@ -19,6 +21,7 @@ pub enum SoundFontError {
         actual: FourCC,
     },
     ListContainsUnknownId(FourCC),
+    InvalidChunkSize(FourCC),
     SampleDataNotFound,
     UnsupportedSampleFormat,
     SubChunkNotFound(FourCC),
@@ -41,6 +44,10 @@ pub enum SoundFontError {
     ZoneNotFound,
     InvalidGeneratorList,
     SanityCheckFailed,
+    UnknownPreset(PresetId),
+    UnknownPresetName(String),
+    DuplicatePreset(PresetId),
+    Cancelled,
 }
 
 impl error::Error for SoundFontError {
@@ -71,6 +78,9 @@ impl fmt::Display for SoundFontError {
             SoundFontError::ListContainsUnknownId(id) => {
                 write!(f, "the INFO list contains an unknown ID '{id}'")
             }
+            SoundFontError::InvalidChunkSize(id) => {
+                write!(f, "the size of the '{id}' chunk is invalid")
+            }
             SoundFontError::SampleDataNotFound => write!(f, "no valid sample data was found"),
             SoundFontError::UnsupportedSampleFormat => write!(f, "SoundFont3 is not yet supported"),
             SoundFontError::SubChunkNotFound(id) => {
@@ -85,7 +95,7 @@ impl fmt::Display for SoundFontError {
                 "the preset with the ID '{preset_id}' contains an invalid instrument ID '{instrument_id}'"
             ),
             SoundFontError::InvalidPreset(preset_id) => {
-                write!(f, "the preset with the ID '{preset_id}' has no zone")
+                write!(f, "the preset with the ID '{preset_id}' has no valid zone")
             }
             SoundFontError::PresetNotFound => write!(f, "no valid preset was found"),
             SoundFontError::InvalidInstrumentList => write!(f, "the instrument list is invalid"),
@@ -99,7 +109,7 @@ impl fmt::Display for SoundFontError {
             SoundFontError::InvalidInstrument(instrument_id) => {
                 write!(
                     f,
-                    "the instrument with the ID '{instrument_id}' has no zone"
+                    "the instrument with the ID '{instrument_id}' has no valid zone"
                 )
             }
             SoundFontError::InstrumentNotFound => write!(f, "no valid instrument was found"),
@@ -110,6 +120,20 @@ impl fmt::Display for SoundFontError {
             SoundFontError::ZoneNotFound => write!(f, "no valid zone was found"),
             SoundFontError::InvalidGeneratorList => write!(f, "the generator list is invalid"),
             SoundFontError::SanityCheckFailed => write!(f, "sanity check failed"),
+            SoundFontError::UnknownPreset(preset_id) => write!(
+                f,
+                "the preset {}:{} was not found",
+                preset_id.bank, preset_id.program
+            ),
+            SoundFontError::UnknownPresetName(name) => {
+                write!(f, "no preset named '{name}' was found")
+            }
+            SoundFontError::DuplicatePreset(preset_id) => write!(
+                f,
+                "the preset {}:{} was added more than once",
+                preset_id.bank, preset_id.program
+            ),
+            SoundFontError::Cancelled => write!(f, "loading was cancelled"),
         }
     }
 }
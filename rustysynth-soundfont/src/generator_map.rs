@@ -0,0 +1,43 @@
+use std::collections::BTreeMap;
+
+use serde::de::{Deserialize, Deserializer, Error};
+use serde::ser::{SerializeMap, Serializer};
+
+use crate::generator_type::GeneratorType;
+
+type Generators = [i16; GeneratorType::COUNT];
+
+/// Serializes the generators of a region as a map from the generator names to the values.
+/// The generators which have the default value are omitted.
+pub(crate) fn serialize<S: Serializer>(
+    gs: &Generators,
+    defaults: &Generators,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    let count = gs.iter().zip(defaults).filter(|(a, b)| a != b).count();
+    let mut map = serializer.serialize_map(Some(count))?;
+    for (i, value) in gs.iter().enumerate() {
+        if *value != defaults[i] {
+            map.serialize_entry(GeneratorType::NAMES[i], value)?;
+        }
+    }
+    map.end()
+}
+
+/// Deserializes the generators of a region.
+/// The generators not present in the map take the default value.
+pub(crate) fn deserialize<'de, D: Deserializer<'de>>(
+    defaults: &Generators,
+    deserializer: D,
+) -> Result<Generators, D::Error> {
+    let values: BTreeMap<String, i16> = BTreeMap::deserialize(deserializer)?;
+
+    let mut gs = *defaults;
+    for (name, value) in values {
+        match GeneratorType::from_name(&name) {
+            Some(index) => gs[index as usize] = value,
+            None => return Err(D::Error::custom(format!("unknown generator '{}'", name))),
+        }
+    }
+    Ok(gs)
+}
@@ -65,4 +65,76 @@ impl GeneratorType {
     pub(crate) const UNUSED_END: u16 = 60;
 
     pub(crate) const COUNT: usize = 61;
+
+    /// The names of the generators, indexed by the generator type.
+    pub(crate) const NAMES: [&'static str; GeneratorType::COUNT] = [
+        "start_address_offset",
+        "end_address_offset",
+        "start_loop_address_offset",
+        "end_loop_address_offset",
+        "start_address_coarse_offset",
+        "modulation_lfo_to_pitch",
+        "vibrato_lfo_to_pitch",
+        "modulation_envelope_to_pitch",
+        "initial_filter_cutoff_frequency",
+        "initial_filter_q",
+        "modulation_lfo_to_filter_cutoff_frequency",
+        "modulation_envelope_to_filter_cutoff_frequency",
+        "end_address_coarse_offset",
+        "modulation_lfo_to_volume",
+        "unused_1",
+        "chorus_effects_send",
+        "reverb_effects_send",
+        "pan",
+        "unused_2",
+        "unused_3",
+        "unused_4",
+        "delay_modulation_lfo",
+        "frequency_modulation_lfo",
+        "delay_vibrato_lfo",
+        "frequency_vibrato_lfo",
+        "delay_modulation_envelope",
+        "attack_modulation_envelope",
+        "hold_modulation_envelope",
+        "decay_modulation_envelope",
+        "sustain_modulation_envelope",
+        "release_modulation_envelope",
+        "key_number_to_modulation_envelope_hold",
+        "key_number_to_modulation_envelope_decay",
+        "delay_volume_envelope",
+        "attack_volume_envelope",
+        "hold_volume_envelope",
+        "decay_volume_envelope",
+        "sustain_volume_envelope",
+        "release_volume_envelope",
+        "key_number_to_volume_envelope_hold",
+        "key_number_to_volume_envelope_decay",
+        "instrument",
+        "reserved_1",
+        "key_range",
+        "velocity_range",
+        "start_loop_address_coarse_offset",
+        "key_number",
+        "velocity",
+        "initial_attenuation",
+        "reserved_2",
+        "end_loop_address_coarse_offset",
+        "coarse_tune",
+        "fine_tune",
+        "sample_id",
+        "sample_modes",
+        "reserved_3",
+        "scale_tuning",
+        "exclusive_class",
+        "overriding_root_key",
+        "unused_5",
+        "unused_end",
+    ];
+
+    pub(crate) fn from_name(name: &str) -> Option<u16> {
+        GeneratorType::NAMES
+            .iter()
+            .position(|value| *value == name)
+            .map(|index| index as u16)
+    }
 }
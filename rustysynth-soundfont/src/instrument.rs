@@ -6,13 +6,14 @@ use crate::zone::Zone;
 
 /// Represents an instrument in the SoundFont.
 #[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Instrument {
     pub(crate) name: String,
     pub(crate) regions: Vec<InstrumentRegion>,
 }
 
 impl Instrument {
-    fn new(
+    pub(crate) fn new(
         info: &InstrumentInfo,
         instrument_id: usize,
         zones: &[Zone],
@@ -20,14 +21,18 @@ impl Instrument {
     ) -> Result<Self, SoundFontError> {
         let name = info.name.clone();
 
+        // An empty zone span is allowed, but a reversed one is not.
         let zone_count = info.zone_end_index - info.zone_start_index + 1;
-        if zone_count <= 0 {
+        if zone_count < 0 {
             return Err(SoundFontError::InvalidInstrument(instrument_id));
         }
 
         let span_start = info.zone_start_index as usize;
         let span_end = span_start + zone_count as usize;
-        let zone_span = &zones[span_start..span_end];
+        let zone_span = match zones.get(span_start..span_end) {
+            Some(value) => value,
+            None => return Err(SoundFontError::InvalidInstrument(instrument_id)),
+        };
         let regions = InstrumentRegion::create(instrument_id, zone_span, samples)?;
 
         Ok(Self { name, regions })
@@ -15,17 +15,74 @@ fn set_parameter(gs: &mut [i16; GeneratorType::COUNT], generator: &Generator) {
     }
 }
 
+pub(crate) const fn default_generators() -> [i16; GeneratorType::COUNT] {
+    let mut gs: [i16; GeneratorType::COUNT] = [0; GeneratorType::COUNT];
+    gs[GeneratorType::INITIAL_FILTER_CUTOFF_FREQUENCY as usize] = 13500;
+    gs[GeneratorType::DELAY_MODULATION_LFO as usize] = -12000;
+    gs[GeneratorType::DELAY_VIBRATO_LFO as usize] = -12000;
+    gs[GeneratorType::DELAY_MODULATION_ENVELOPE as usize] = -12000;
+    gs[GeneratorType::ATTACK_MODULATION_ENVELOPE as usize] = -12000;
+    gs[GeneratorType::HOLD_MODULATION_ENVELOPE as usize] = -12000;
+    gs[GeneratorType::DECAY_MODULATION_ENVELOPE as usize] = -12000;
+    gs[GeneratorType::RELEASE_MODULATION_ENVELOPE as usize] = -12000;
+    gs[GeneratorType::DELAY_VOLUME_ENVELOPE as usize] = -12000;
+    gs[GeneratorType::ATTACK_VOLUME_ENVELOPE as usize] = -12000;
+    gs[GeneratorType::HOLD_VOLUME_ENVELOPE as usize] = -12000;
+    gs[GeneratorType::DECAY_VOLUME_ENVELOPE as usize] = -12000;
+    gs[GeneratorType::RELEASE_VOLUME_ENVELOPE as usize] = -12000;
+    gs[GeneratorType::KEY_RANGE as usize] = 0x7F00;
+    gs[GeneratorType::VELOCITY_RANGE as usize] = 0x7F00;
+    gs[GeneratorType::KEY_NUMBER as usize] = -1;
+    gs[GeneratorType::VELOCITY as usize] = -1;
+    gs[GeneratorType::SCALE_TUNING as usize] = 100;
+    gs[GeneratorType::OVERRIDING_ROOT_KEY as usize] = -1;
+    gs
+}
+
+#[cfg(feature = "serde")]
+fn serialize_generators<S: serde::Serializer>(
+    gs: &[i16; GeneratorType::COUNT],
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    crate::generator_map::serialize(gs, &default_generators(), serializer)
+}
+
+#[cfg(feature = "serde")]
+fn deserialize_generators<'de, D: serde::Deserializer<'de>>(
+    deserializer: D,
+) -> Result<[i16; GeneratorType::COUNT], D::Error> {
+    crate::generator_map::deserialize(&default_generators(), deserializer)
+}
+
 /// Represents an instrument region.
 /// An instrument region contains all the parameters necessary to synthesize a note.
+/// When serialized, the sample is referenced by the sample ID generator,
+/// and the copy of the sample header is restored by `SoundFont::rebuild`.
 #[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct InstrumentRegion {
+    #[cfg_attr(
+        feature = "serde",
+        serde(
+            rename = "generators",
+            serialize_with = "serialize_generators",
+            deserialize_with = "deserialize_generators"
+        )
+    )]
     pub(crate) gs: [i16; GeneratorType::COUNT],
+    #[cfg_attr(feature = "serde", serde(skip))]
     pub(crate) sample_start: i32,
+    #[cfg_attr(feature = "serde", serde(skip))]
     pub(crate) sample_end: i32,
+    #[cfg_attr(feature = "serde", serde(skip))]
     pub(crate) sample_start_loop: i32,
+    #[cfg_attr(feature = "serde", serde(skip))]
     pub(crate) sample_end_loop: i32,
+    #[cfg_attr(feature = "serde", serde(skip))]
     pub(crate) sample_sample_rate: i32,
+    #[cfg_attr(feature = "serde", serde(skip))]
     pub(crate) sample_original_pitch: i32,
+    #[cfg_attr(feature = "serde", serde(skip))]
     pub(crate) sample_pitch_correction: i32,
 }
 
@@ -36,26 +93,7 @@ impl InstrumentRegion {
         local: &Zone,
         samples: &[SampleHeader],
     ) -> Result<Self, SoundFontError> {
-        let mut gs: [i16; GeneratorType::COUNT] = [0; GeneratorType::COUNT];
-        gs[GeneratorType::INITIAL_FILTER_CUTOFF_FREQUENCY as usize] = 13500;
-        gs[GeneratorType::DELAY_MODULATION_LFO as usize] = -12000;
-        gs[GeneratorType::DELAY_VIBRATO_LFO as usize] = -12000;
-        gs[GeneratorType::DELAY_MODULATION_ENVELOPE as usize] = -12000;
-        gs[GeneratorType::ATTACK_MODULATION_ENVELOPE as usize] = -12000;
-        gs[GeneratorType::HOLD_MODULATION_ENVELOPE as usize] = -12000;
-        gs[GeneratorType::DECAY_MODULATION_ENVELOPE as usize] = -12000;
-        gs[GeneratorType::RELEASE_MODULATION_ENVELOPE as usize] = -12000;
-        gs[GeneratorType::DELAY_VOLUME_ENVELOPE as usize] = -12000;
-        gs[GeneratorType::ATTACK_VOLUME_ENVELOPE as usize] = -12000;
-        gs[GeneratorType::HOLD_VOLUME_ENVELOPE as usize] = -12000;
-        gs[GeneratorType::DECAY_VOLUME_ENVELOPE as usize] = -12000;
-        gs[GeneratorType::RELEASE_VOLUME_ENVELOPE as usize] = -12000;
-        gs[GeneratorType::KEY_RANGE as usize] = 0x7F00;
-        gs[GeneratorType::VELOCITY_RANGE as usize] = 0x7F00;
-        gs[GeneratorType::KEY_NUMBER as usize] = -1;
-        gs[GeneratorType::VELOCITY as usize] = -1;
-        gs[GeneratorType::SCALE_TUNING as usize] = 100;
-        gs[GeneratorType::OVERRIDING_ROOT_KEY as usize] = -1;
+        let mut gs = default_generators();
 
         for generator in global.generators.iter() {
             set_parameter(&mut gs, generator);
@@ -72,18 +110,31 @@ impl InstrumentRegion {
                 sample_id,
             });
         }
-        let sample = &samples[sample_id];
 
-        Ok(Self {
+        let mut region = Self {
             gs,
-            sample_start: sample.start,
-            sample_end: sample.end,
-            sample_start_loop: sample.start_loop,
-            sample_end_loop: sample.end_loop,
-            sample_sample_rate: sample.sample_rate,
-            sample_original_pitch: sample.original_pitch as i32,
-            sample_pitch_correction: sample.pitch_correction as i32,
-        })
+            sample_start: 0,
+            sample_end: 0,
+            sample_start_loop: 0,
+            sample_end_loop: 0,
+            sample_sample_rate: 0,
+            sample_original_pitch: 0,
+            sample_pitch_correction: 0,
+        };
+        region.set_sample(&samples[sample_id]);
+
+        Ok(region)
+    }
+
+    /// Copies the parameters of the sample used by the region.
+    pub(crate) fn set_sample(&mut self, sample: &SampleHeader) {
+        self.sample_start = sample.start;
+        self.sample_end = sample.end;
+        self.sample_start_loop = sample.start_loop;
+        self.sample_end_loop = sample.end_loop;
+        self.sample_sample_rate = sample.sample_rate;
+        self.sample_original_pitch = sample.original_pitch as i32;
+        self.sample_pitch_correction = sample.pitch_correction as i32;
     }
 
     pub(crate) fn create(
@@ -91,12 +142,19 @@ impl InstrumentRegion {
         zones: &[Zone],
         samples: &[SampleHeader],
     ) -> Result<Vec<InstrumentRegion>, SoundFontError> {
+        // An instrument without zones has no regions.
+        let Some(first) = zones.first() else {
+            return Ok(Vec::new());
+        };
+
         // Is the first one the global zone?
-        if zones[0].generators.is_empty()
-            || zones[0].generators.last().unwrap().generator_type != GeneratorType::SAMPLE_ID
+        if first
+            .generators
+            .last()
+            .is_none_or(|generator| generator.generator_type != GeneratorType::SAMPLE_ID)
         {
             // The first one is the global zone.
-            let global = &zones[0];
+            let global = first;
 
             // The global zone is regarded as the base setting of subsequent zones.
             let count = zones.len() - 1;
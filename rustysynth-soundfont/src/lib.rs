@@ -5,71 +5,124 @@ mod four_cc;
 mod read_counter;
 
 mod generator;
+#[cfg(feature = "serde")]
+mod generator_map;
 mod generator_type;
 mod instrument;
 mod instrument_info;
 mod instrument_region;
+mod partial_load;
 mod preset;
 mod preset_info;
 mod preset_region;
+mod preset_selector;
+mod resampler;
+mod region_lookup;
 mod region_pair;
+mod sample_export;
 mod sample_header;
 mod soundfont;
 mod soundfont_info;
+mod soundfont_loader;
+mod soundfont_merger;
 mod soundfont_parameters;
 mod soundfont_sampledata;
 mod soundfont_version;
+mod soundfont_writer;
 mod zone;
 mod zone_info;
 
+pub use self::preset_selector::PresetSelector;
+pub use self::sample_export::{SampleExport, SampleExporter};
 pub use self::soundfont::SoundFont;
+pub use self::soundfont_loader::{LoadPhase, LoadProgress, SoundFontLoader};
+pub use self::soundfont_merger::SoundFontMerger;
 use anyhow::{anyhow, Result};
 use instrument::Instrument;
 use preset::Preset;
+use region_lookup::RegionLookup;
 use region_pair::RegionPair;
-use rustysynth::{SoundSource, View};
-use std::{collections::HashMap, sync::Arc};
+use rustysynth::{PresetDescriptor, PresetId, SoundSource, WaveData, WaveView};
+use std::collections::HashMap;
+
+/// Specifies how `SoundFontProc` prepares the SoundFont.
+#[derive(Debug, Clone, Default)]
+pub struct SoundFontProcSettings {
+    /// If `true`, every sample is resampled to the output sample rate at load time
+    /// instead of being resampled on the fly during playback.
+    pub resample: bool,
+}
 
 pub struct SoundFontProc {
     presets: Vec<Preset>,
     instruments: Vec<Instrument>,
-    preset_lookup: HashMap<i32, usize>,
+    preset_lookup: HashMap<PresetId, usize>,
+    region_lookups: Vec<RegionLookup>,
     default_preset: usize,
-    wave_data: Arc<[i16]>,
+    wave_data: WaveData,
 }
 
 impl SoundFontProc {
     pub fn new(sound_font: SoundFont) -> Self {
+        Self::with_settings(sound_font, &SoundFontProcSettings::default())
+    }
+
+    /// Creates a sound source from the SoundFont.
+    ///
+    /// # Arguments
+    ///
+    /// * `sound_font` - The SoundFont to play.
+    /// * `settings` - How the SoundFont is prepared for playback.
+    pub fn with_settings(mut sound_font: SoundFont, settings: &SoundFontProcSettings) -> Self {
+        if settings.resample {
+            resampler::resample_sound_font(&mut sound_font, rustysynth::SAMPLE_RATE);
+        }
+
         let mut preset_lookup = HashMap::new();
 
-        let mut min_preset_id = i32::MAX;
+        let mut min_preset_id: Option<PresetId> = None;
         let mut default_preset: usize = 0;
-        for i in 0..sound_font.presets.len() {
-            let preset = &sound_font.presets[i];
-
-            // The preset ID is Int32, where the upper 16 bits represent the bank number
-            // and the lower 16 bits represent the patch number.
-            // This ID is used to search for presets by the combination of bank number
-            // and patch number.
-            let preset_id = (preset.bank_number << 16) | preset.patch_number;
+        for (i, preset) in sound_font.presets.iter().enumerate() {
+            // Presets whose numbers cannot be selected by MIDI are not searchable.
+            let Some(preset_id) = preset.get_preset_id() else {
+                continue;
+            };
+            // If two presets have the same ID, the last one is used.
             preset_lookup.insert(preset_id, i);
 
             // The preset with the minimum ID number will be default.
             // If the SoundFont is GM compatible, the piano will be chosen.
-            if preset_id < min_preset_id {
+            if min_preset_id.is_none_or(|min| preset_id < min) {
                 default_preset = i;
-                min_preset_id = preset_id;
+                min_preset_id = Some(preset_id);
             }
         }
 
+        let region_lookups = sound_font
+            .presets
+            .iter()
+            .map(|preset| RegionLookup::new(preset, &sound_font.instruments))
+            .collect();
+
         Self {
             presets: sound_font.presets,
             instruments: sound_font.instruments,
             preset_lookup,
+            region_lookups,
             default_preset,
-            wave_data: Arc::from(sound_font.wave_data.into_boxed_slice()),
+            wave_data: sound_font.wave_data,
         }
     }
+
+    fn find_preset_index(&self, preset_id: PresetId) -> usize {
+        // Try fallback to the GM sound set.
+        // If no corresponding preset was found, use the default one...
+        self.preset_lookup
+            .get(&preset_id)
+            .or_else(|| self.preset_lookup.get(&preset_id.get_gm_fallback()))
+            .copied()
+            .unwrap_or(self.default_preset)
+    }
 }
 
 impl From<SoundFont> for SoundFontProc {
@@ -80,61 +133,65 @@ impl From<SoundFont> for SoundFontProc {
 
 impl SoundSource for SoundFontProc {
     #[allow(refining_impl_trait)]
-    fn get_sound(&mut self, preset_id: u16, key: i32, velocity: i32) -> Result<RegionPair> {
-        let bank_id = (preset_id >> 8) as i32;
-        let patch_id = (preset_id & 0xFF) as i32;
-        let preset_id = (bank_id << 16) | patch_id;
-        let mut preset = self.default_preset;
-        match self.preset_lookup.get(&preset_id) {
-            Some(value) => preset = *value,
-            None => {
-                // Try fallback to the GM sound set.
-                // Normally, the given patch number + the bank number 0 will work.
-                // For drums (bank number >= 128), it seems to be better to select the standard set (128:0).
-                let gm_preset_id = if bank_id < 128 { patch_id } else { 128 << 16 };
-
-                // If no corresponding preset was found. Use the default one...
-                if let Some(value) = self.preset_lookup.get(&gm_preset_id) {
-                    preset = *value
-                }
-            }
-        }
-
-        let preset = &self.presets[preset];
-        for preset in preset.regions.iter() {
-            if preset.contains(key, velocity) {
-                let instrument = &self.instruments[preset.instrument];
-                for instrument in instrument.regions.iter() {
-                    if instrument.contains(key, velocity) {
-                        let wave_data = View {
-                            data: self.wave_data.clone(),
-                            start: instrument.sample_start as usize,
-                            end: instrument.sample_end as usize,
-                        };
-                        let region_pair = RegionPair {
-                            preset,
-                            instrument,
-                            wave_data,
-                        };
-                        // XXX In the original implementation, at this point, a
-                        // voice would start, which means that one "note_on"
-                        // could result in many voices if the key/vel pair were
-                        // in multiple preset regions.
-                        //
-                        // This could be supported by changing the interface to
-                        // return a Vec<Sound> and then the caller would iterate
-                        // through them and start all as appropriate.
-                        return Ok(region_pair);
-                    }
-                }
-            }
+    fn get_sound(
+        &mut self,
+        preset_id: PresetId,
+        key: i32,
+        velocity: i32,
+    ) -> Result<RegionPair<'_>> {
+        let preset_index = self.find_preset_index(preset_id);
+        if let Some((i, j)) = self.region_lookups[preset_index].find(key, velocity) {
+            let preset = &self.presets[preset_index].regions[i];
+            let instrument = &self.instruments[preset.instrument].regions[j];
+            let wave_data = WaveView {
+                data: self.wave_data.clone(),
+                start: instrument.sample_start as usize,
+                end: instrument.sample_end as usize,
+            };
+            // XXX In the original implementation, at this point, a
+            // voice would start, which means that one "note_on"
+            // could result in many voices if the key/vel pair were
+            // in multiple preset regions.
+            //
+            // This could be supported by changing the interface to
+            // return a Vec<Sound> and then the caller would iterate
+            // through them and start all as appropriate.
+            return Ok(RegionPair {
+                preset,
+                instrument,
+                wave_data,
+            });
         }
         Err(anyhow!(
             "No regions found for bank_id: {}, patch_id: {}, key: {}, velocity: {}",
-            bank_id,
-            patch_id,
+            preset_id.bank,
+            preset_id.program,
             key,
             velocity
         ))
     }
+
+    fn get_presets(&self) -> Vec<PresetDescriptor> {
+        let mut presets: Vec<PresetDescriptor> = self
+            .preset_lookup
+            .iter()
+            .map(|(id, i)| PresetDescriptor {
+                id: *id,
+                name: self.presets[*i].name.clone(),
+            })
+            .collect();
+        presets.sort_by_key(|preset| preset.id);
+        presets
+    }
+
+    fn resolve_preset(&self, preset_id: PresetId) -> Option<PresetId> {
+        if self.presets.is_empty() {
+            return None;
+        }
+        self.presets[self.find_preset_index(preset_id)].get_preset_id()
+    }
+
+    fn contains_preset(&self, preset_id: PresetId) -> bool {
+        self.preset_lookup.contains_key(&preset_id)
+    }
 }
@@ -0,0 +1,175 @@
+use std::collections::HashMap;
+use std::ops::Range;
+
+use rustysynth::{LoopMode, WaveData};
+
+use crate::error::SoundFontError;
+use crate::generator_type::GeneratorType;
+use crate::preset::Preset;
+use crate::preset_selector::PresetSelector;
+use crate::sample_header::SampleHeader;
+use crate::soundfont::SoundFont;
+
+/// Checks that every selector matches at least one preset.
+pub(crate) fn check_selectors(
+    presets: &[Preset],
+    selectors: &[PresetSelector],
+) -> Result<(), SoundFontError> {
+    for selector in selectors {
+        if !presets.iter().any(|preset| selector.matches(preset)) {
+            return Err(match selector {
+                PresetSelector::Id(id) => SoundFontError::UnknownPreset(*id),
+                PresetSelector::Name(name) => SoundFontError::UnknownPresetName(name.clone()),
+            });
+        }
+    }
+
+    Ok(())
+}
+
+/// Keeps the presets chosen by the selectors, in their original order.
+/// Returns the kept presets and the IDs of the instruments they use.
+/// The instruments of the kept presets are renumbered in the order of the returned IDs.
+pub(crate) fn select_presets(
+    presets: Vec<Preset>,
+    selectors: &[PresetSelector],
+) -> Result<(Vec<Preset>, Vec<usize>), SoundFontError> {
+    check_selectors(&presets, selectors)?;
+
+    let mut selected: Vec<Preset> = Vec::new();
+    let mut instrument_ids: Vec<usize> = Vec::new();
+    let mut instrument_map: HashMap<usize, usize> = HashMap::new();
+
+    for mut preset in presets {
+        if !selectors.iter().any(|selector| selector.matches(&preset)) {
+            continue;
+        }
+
+        for region in &mut preset.regions {
+            let source = region.instrument;
+            let instrument = *instrument_map.entry(source).or_insert_with(|| {
+                instrument_ids.push(source);
+                instrument_ids.len() - 1
+            });
+            region.instrument = instrument;
+            region.gs[GeneratorType::INSTRUMENT as usize] = instrument as i16;
+        }
+
+        selected.push(preset);
+    }
+
+    Ok((selected, instrument_ids))
+}
+
+/// Replaces the sample data with the parts used by the instruments, and removes the unused samples.
+/// Each sample is copied with the range reached by the address offsets of its regions,
+/// and with its loop if any region plays the loop.
+///
+/// # Arguments
+///
+/// * `sound_font` - The SoundFont whose instruments are already selected.
+/// * `source_length` - The number of samples in the source data.
+/// * `read` - The function which reads a range of the source data.
+pub(crate) fn compact_samples<F>(
+    sound_font: &mut SoundFont,
+    source_length: usize,
+    mut read: F,
+) -> Result<(), SoundFontError>
+where
+    F: FnMut(Range<usize>) -> Result<Vec<i16>, SoundFontError>,
+{
+    let headers = &sound_font.sample_headers;
+
+    // The range of the source data used by each sample.
+    let mut spans: Vec<Option<(i64, i64)>> = vec![None; headers.len()];
+    for instrument in &sound_font.instruments {
+        for region in &instrument.regions {
+            let sample_id = region.get_sample_id();
+            let header = &headers[sample_id];
+
+            let mut positions = vec![
+                header.start as i64,
+                header.end as i64,
+                header.start as i64 + region.get_start_address_offset() as i64,
+                header.end as i64 + region.get_end_address_offset() as i64,
+            ];
+            if region.get_sample_modes() != LoopMode::NoLoop {
+                positions.extend([
+                    header.start_loop as i64,
+                    header.end_loop as i64,
+                    header.start_loop as i64 + region.get_start_loop_address_offset() as i64,
+                    header.end_loop as i64 + region.get_end_loop_address_offset() as i64,
+                ]);
+            }
+
+            let start = positions.iter().min().copied().unwrap_or_default();
+            let end = positions.iter().max().copied().unwrap_or_default();
+            spans[sample_id] = Some(match spans[sample_id] {
+                Some((first, last)) => (first.min(start), last.max(end)),
+                None => (start, end),
+            });
+        }
+    }
+
+    let mut wave_data: Vec<i16> = Vec::new();
+    let mut new_headers: Vec<SampleHeader> = Vec::new();
+    let mut sample_map: Vec<Option<usize>> = vec![None; headers.len()];
+
+    for (sample_id, span) in spans.iter().enumerate() {
+        let Some((start, end)) = *span else {
+            continue;
+        };
+        let start = start.clamp(0, source_length as i64) as usize;
+        let end = (end.clamp(0, source_length as i64) as usize).max(start);
+
+        let new_start = wave_data.len() as i64;
+        wave_data.extend(read(start..end)?);
+        let new_end = wave_data.len() as i64;
+        // Each sample is followed by at least 46 zero samples in the SoundFont format.
+        wave_data.extend_from_slice(&[0; 46]);
+
+        // The loop of a sample which is never looped may lie outside of the copied range.
+        let delta = new_start - start as i64;
+        let map = |position: i32| (position as i64 + delta).clamp(new_start, new_end) as i32;
+
+        let header = &headers[sample_id];
+        sample_map[sample_id] = Some(new_headers.len());
+        new_headers.push(SampleHeader {
+            name: header.name.clone(),
+            start: map(header.start),
+            end: map(header.end),
+            start_loop: map(header.start_loop),
+            end_loop: map(header.end_loop),
+            sample_rate: header.sample_rate,
+            original_pitch: header.original_pitch,
+            pitch_correction: header.pitch_correction,
+            link: header.link,
+            sample_type: header.sample_type,
+        });
+    }
+
+    for header in &mut new_headers {
+        if !header.is_linked() {
+            continue;
+        }
+
+        match sample_map.get(header.link as usize).copied().flatten() {
+            Some(link) => header.link = link as u16,
+            None => header.unlink(),
+        }
+    }
+
+    for instrument in &mut sound_font.instruments {
+        for region in &mut instrument.regions {
+            if let Some(sample_id) = sample_map[region.get_sample_id()] {
+                region.gs[GeneratorType::SAMPLE_ID as usize] = sample_id as i16;
+                region.set_sample(&new_headers[sample_id]);
+            }
+        }
+    }
+
+    sound_font.sample_headers = new_headers;
+    sound_font.set_wave_data(WaveData::from(wave_data));
+
+    Ok(())
+}
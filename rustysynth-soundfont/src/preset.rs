@@ -1,11 +1,12 @@
 use crate::error::SoundFontError;
-use crate::instrument::Instrument;
 use crate::preset_info::PresetInfo;
 use crate::preset_region::PresetRegion;
 use crate::zone::Zone;
+use rustysynth::PresetId;
 
 /// Represents a preset in the SoundFont.
 #[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Preset {
     pub(crate) name: String,
     pub(crate) patch_number: i32,
@@ -21,19 +22,23 @@ impl Preset {
         info: &PresetInfo,
         preset_id: usize,
         zones: &[Zone],
-        instruments: &[Instrument],
+        instrument_count: usize,
     ) -> Result<Self, SoundFontError> {
         let name = info.name.clone();
 
+        // An empty zone span is allowed, but a reversed one is not.
         let zone_count = info.zone_end_index - info.zone_start_index + 1;
-        if zone_count <= 0 {
+        if zone_count < 0 {
             return Err(SoundFontError::InvalidPreset(preset_id));
         }
 
         let span_start = info.zone_start_index as usize;
         let span_end = span_start + zone_count as usize;
-        let zone_span = &zones[span_start..span_end];
-        let regions = PresetRegion::create(preset_id, zone_span, instruments)?;
+        let zone_span = match zones.get(span_start..span_end) {
+            Some(value) => value,
+            None => return Err(SoundFontError::InvalidPreset(preset_id)),
+        };
+        let regions = PresetRegion::create(preset_id, zone_span, instrument_count)?;
 
         Ok(Self {
             name,
@@ -49,7 +54,7 @@ impl Preset {
     pub(crate) fn create(
         infos: &[PresetInfo],
         zones: &[Zone],
-        instruments: &[Instrument],
+        instrument_count: usize,
     ) -> Result<Vec<Preset>, SoundFontError> {
         if infos.len() <= 1 {
             return Err(SoundFontError::PresetNotFound);
@@ -60,7 +65,7 @@ impl Preset {
 
         let mut presets: Vec<Preset> = Vec::new();
         for (preset_id, info) in infos.iter().take(count).enumerate() {
-            presets.push(Preset::new(info, preset_id, zones, instruments)?);
+            presets.push(Preset::new(info, preset_id, zones, instrument_count)?);
         }
 
         Ok(presets)
@@ -81,6 +86,14 @@ impl Preset {
         self.bank_number
     }
 
+    /// Gets the ID used to select the preset by MIDI.
+    /// Returns `None` if the bank or patch number is out of range.
+    pub fn get_preset_id(&self) -> Option<PresetId> {
+        let bank = u16::try_from(self.bank_number).ok()?;
+        let program = u8::try_from(self.patch_number).ok().filter(|p| *p < 128)?;
+        Some(PresetId::new(bank, program))
+    }
+
     /// Gets the library info.
     pub fn get_library(&self) -> i32 {
         self.library
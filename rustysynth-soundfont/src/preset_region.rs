@@ -1,7 +1,6 @@
 use crate::error::SoundFontError;
 use crate::generator::Generator;
 use crate::generator_type::GeneratorType;
-use crate::instrument::Instrument;
 use crate::zone::Zone;
 use rustysynth::soundfont_math::*;
 
@@ -14,11 +13,48 @@ fn set_parameter(gs: &mut [i16; GeneratorType::COUNT], generator: &Generator) {
     }
 }
 
+pub(crate) const fn default_generators() -> [i16; GeneratorType::COUNT] {
+    let mut gs: [i16; GeneratorType::COUNT] = [0; GeneratorType::COUNT];
+    gs[GeneratorType::KEY_RANGE as usize] = 0x7F00;
+    gs[GeneratorType::VELOCITY_RANGE as usize] = 0x7F00;
+    gs
+}
+
+pub(crate) fn get_instrument_id(gs: &[i16; GeneratorType::COUNT]) -> usize {
+    gs[GeneratorType::INSTRUMENT as usize] as usize
+}
+
+#[cfg(feature = "serde")]
+fn serialize_generators<S: serde::Serializer>(
+    gs: &[i16; GeneratorType::COUNT],
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    crate::generator_map::serialize(gs, &default_generators(), serializer)
+}
+
+#[cfg(feature = "serde")]
+fn deserialize_generators<'de, D: serde::Deserializer<'de>>(
+    deserializer: D,
+) -> Result<[i16; GeneratorType::COUNT], D::Error> {
+    crate::generator_map::deserialize(&default_generators(), deserializer)
+}
+
 /// Represents a preset region.
 /// A preset region indicates how the parameters of the instrument should be modified in the preset.
+/// When serialized, the instrument is referenced by the instrument generator.
 #[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct PresetRegion {
+    #[cfg_attr(
+        feature = "serde",
+        serde(
+            rename = "generators",
+            serialize_with = "serialize_generators",
+            deserialize_with = "deserialize_generators"
+        )
+    )]
     pub(crate) gs: [i16; GeneratorType::COUNT],
+    #[cfg_attr(feature = "serde", serde(skip))]
     pub(crate) instrument: usize,
 }
 
@@ -27,11 +63,9 @@ impl PresetRegion {
         preset_id: usize,
         global: &Zone,
         local: &Zone,
-        samples: &[Instrument],
+        instrument_count: usize,
     ) -> Result<Self, SoundFontError> {
-        let mut gs: [i16; GeneratorType::COUNT] = [0; GeneratorType::COUNT];
-        gs[GeneratorType::KEY_RANGE as usize] = 0x7F00;
-        gs[GeneratorType::VELOCITY_RANGE as usize] = 0x7F00;
+        let mut gs = default_generators();
 
         for generator in global.generators.iter() {
             set_parameter(&mut gs, generator);
@@ -41,8 +75,8 @@ impl PresetRegion {
             set_parameter(&mut gs, generator);
         }
 
-        let instrument_id = gs[GeneratorType::INSTRUMENT as usize] as usize;
-        if instrument_id >= samples.len() {
+        let instrument_id = get_instrument_id(&gs);
+        if instrument_id >= instrument_count {
             return Err(SoundFontError::InvalidInstrumentId {
                 preset_id,
                 instrument_id,
@@ -58,14 +92,21 @@ impl PresetRegion {
     pub(crate) fn create(
         preset_id: usize,
         zones: &[Zone],
-        instruments: &[Instrument],
+        instrument_count: usize,
     ) -> Result<Vec<PresetRegion>, SoundFontError> {
+        // A preset without zones has no regions.
+        let Some(first) = zones.first() else {
+            return Ok(Vec::new());
+        };
+
         // Is the first one the global zone?
-        if zones[0].generators.is_empty()
-            || zones[0].generators.last().unwrap().generator_type != GeneratorType::INSTRUMENT
+        if first
+            .generators
+            .last()
+            .is_none_or(|generator| generator.generator_type != GeneratorType::INSTRUMENT)
         {
             // The first one is the global zone.
-            let global = &zones[0];
+            let global = first;
 
             // The global zone is regarded as the base setting of subsequent zones.
             let count = zones.len() - 1;
@@ -75,7 +116,7 @@ impl PresetRegion {
                     preset_id,
                     global,
                     &zones[i + 1],
-                    instruments,
+                    instrument_count,
                 )?);
             }
 
@@ -89,7 +130,7 @@ impl PresetRegion {
                     preset_id,
                     &Zone::empty(),
                     zone,
-                    instruments,
+                    instrument_count,
                 )?);
             }
 
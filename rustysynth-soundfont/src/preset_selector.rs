@@ -0,0 +1,33 @@
+use rustysynth::PresetId;
+
+use crate::preset::Preset;
+
+/// Specifies a preset to keep when a SoundFont is partially loaded.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PresetSelector {
+    /// Selects the preset with the bank and program numbers.
+    Id(PresetId),
+    /// Selects every preset with the exact name.
+    Name(String),
+}
+
+impl PresetSelector {
+    pub(crate) fn matches(&self, preset: &Preset) -> bool {
+        match self {
+            PresetSelector::Id(id) => preset.get_preset_id() == Some(*id),
+            PresetSelector::Name(name) => preset.name == *name,
+        }
+    }
+}
+
+impl From<PresetId> for PresetSelector {
+    fn from(id: PresetId) -> Self {
+        PresetSelector::Id(id)
+    }
+}
+
+impl From<&str> for PresetSelector {
+    fn from(name: &str) -> Self {
+        PresetSelector::Name(name.to_string())
+    }
+}
@@ -1,24 +1,71 @@
+use std::io;
 use std::io::Read;
 
 pub(crate) struct ReadCounter<'a, R: Read> {
     reader: &'a mut R,
     count: usize,
+    progress: Option<Progress<'a>>,
+}
+
+// The callback receives the number of bytes read so far.
+// If it returns an error, the read which triggered the report fails with the error.
+struct Progress<'a> {
+    callback: &'a mut dyn FnMut(usize) -> io::Result<()>,
+    interval: usize,
+    next_report: usize,
 }
 
 impl<'a, R: Read> ReadCounter<'a, R> {
     pub(crate) fn new(reader: &'a mut R) -> Self {
-        Self { reader, count: 0 }
+        Self {
+            reader,
+            count: 0,
+            progress: None,
+        }
+    }
+
+    /// Creates a counter which reports the progress each time `interval` bytes have been read.
+    pub(crate) fn with_progress(
+        reader: &'a mut R,
+        interval: usize,
+        callback: &'a mut dyn FnMut(usize) -> io::Result<()>,
+    ) -> Self {
+        Self {
+            reader,
+            count: 0,
+            progress: Some(Progress {
+                callback,
+                interval,
+                next_report: interval,
+            }),
+        }
     }
 
     pub(crate) fn bytes_read(&self) -> usize {
         self.count
     }
+
+    /// Reports the progress now, and restarts the interval.
+    pub(crate) fn report_progress(&mut self) -> io::Result<()> {
+        match &mut self.progress {
+            Some(progress) => {
+                progress.next_report = self.count + progress.interval;
+                (progress.callback)(self.count)
+            }
+            None => Ok(()),
+        }
+    }
 }
 
 impl<R: Read> Read for ReadCounter<'_, R> {
     fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
         let len = self.reader.read(buf)?;
         self.count += len;
+        if let Some(progress) = &self.progress
+            && self.count >= progress.next_report
+        {
+            self.report_progress()?;
+        }
         Ok(len)
     }
 }
@@ -0,0 +1,130 @@
+use crate::instrument::Instrument;
+use crate::preset::Preset;
+
+const KEY_COUNT: usize = 128;
+const VELOCITY_COUNT: i32 = 128;
+
+/// A range of velocities sharing the same regions.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Segment {
+    // The last velocity covered by the segment.
+    velocity_end: u8,
+    // The indices of the preset region and the instrument region.
+    regions: Option<(u32, u32)>,
+}
+
+/// Maps a key and a velocity to the regions of a preset which play the note.
+/// The table is built once so that finding the regions does not depend on the number of zones.
+#[derive(Debug)]
+pub(crate) struct RegionLookup {
+    // The segments of the key N are stored in segments[key_offsets[N]..key_offsets[N + 1]].
+    key_offsets: Box<[u32]>,
+    segments: Box<[Segment]>,
+}
+
+impl RegionLookup {
+    pub(crate) fn new(preset: &Preset, instruments: &[Instrument]) -> Self {
+        let mut key_offsets: Vec<u32> = Vec::with_capacity(KEY_COUNT + 1);
+        let mut segments: Vec<Segment> = Vec::new();
+        let mut boundaries: Vec<i32> = Vec::new();
+
+        for key in 0..KEY_COUNT as i32 {
+            key_offsets.push(segments.len() as u32);
+
+            // The result can only change where a velocity range starts or ends.
+            boundaries.clear();
+            boundaries.push(0);
+            for preset_region in preset.regions.iter() {
+                if !contains_key(
+                    preset_region.get_key_range_start(),
+                    preset_region.get_key_range_end(),
+                    key,
+                ) {
+                    continue;
+                }
+                boundaries.push(preset_region.get_velocity_range_start());
+                boundaries.push(preset_region.get_velocity_range_end() + 1);
+
+                let instrument = &instruments[preset_region.instrument];
+                for instrument_region in instrument.regions.iter() {
+                    if contains_key(
+                        instrument_region.get_key_range_start(),
+                        instrument_region.get_key_range_end(),
+                        key,
+                    ) {
+                        boundaries.push(instrument_region.get_velocity_range_start());
+                        boundaries.push(instrument_region.get_velocity_range_end() + 1);
+                    }
+                }
+            }
+            boundaries.retain(|velocity| (0..VELOCITY_COUNT).contains(velocity));
+            boundaries.sort_unstable();
+            boundaries.dedup();
+
+            for (i, &velocity_start) in boundaries.iter().enumerate() {
+                let velocity_end = boundaries.get(i + 1).map_or(VELOCITY_COUNT, |end| *end) - 1;
+                let regions = find_regions(preset, instruments, key, velocity_start);
+                match segments.last_mut() {
+                    // Merge the adjacent segments which play the same regions.
+                    Some(last) if i > 0 && last.regions == regions => {
+                        last.velocity_end = velocity_end as u8;
+                    }
+                    _ => segments.push(Segment {
+                        velocity_end: velocity_end as u8,
+                        regions,
+                    }),
+                }
+            }
+        }
+        key_offsets.push(segments.len() as u32);
+
+        Self {
+            key_offsets: key_offsets.into_boxed_slice(),
+            segments: segments.into_boxed_slice(),
+        }
+    }
+
+    /// Finds the regions which play the note.
+    /// Returns the indices of the preset region and the instrument region.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key of a note.
+    /// * `velocity` - The velocity of a note.
+    pub(crate) fn find(&self, key: i32, velocity: i32) -> Option<(usize, usize)> {
+        if !(0..KEY_COUNT as i32).contains(&key) || !(0..VELOCITY_COUNT).contains(&velocity) {
+            return None;
+        }
+
+        let start = self.key_offsets[key as usize] as usize;
+        let end = self.key_offsets[key as usize + 1] as usize;
+        let segments = &self.segments[start..end];
+        let index = segments.partition_point(|segment| (segment.velocity_end as i32) < velocity);
+        let (preset_region, instrument_region) = segments.get(index)?.regions?;
+        Some((preset_region as usize, instrument_region as usize))
+    }
+}
+
+fn contains_key(start: i32, end: i32, key: i32) -> bool {
+    start <= key && key <= end
+}
+
+// The first instrument region found in the first matching preset region wins.
+fn find_regions(
+    preset: &Preset,
+    instruments: &[Instrument],
+    key: i32,
+    velocity: i32,
+) -> Option<(u32, u32)> {
+    for (i, preset_region) in preset.regions.iter().enumerate() {
+        if preset_region.contains(key, velocity) {
+            let instrument = &instruments[preset_region.instrument];
+            for (j, instrument_region) in instrument.regions.iter().enumerate() {
+                if instrument_region.contains(key, velocity) {
+                    return Some((i as u32, j as u32));
+                }
+            }
+        }
+    }
+    None
+}
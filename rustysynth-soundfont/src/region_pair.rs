@@ -2,16 +2,20 @@ use crate::generator_type::GeneratorType;
 use crate::instrument_region::InstrumentRegion;
 use crate::preset_region::PresetRegion;
 use rustysynth::soundfont_math::*;
-use rustysynth::{LoopMode, Sound, View};
+use rustysynth::{LoopMode, Sound, View, WaveView};
 
 pub struct RegionPair<'a> {
     pub(crate) preset: &'a PresetRegion,
     pub(crate) instrument: &'a InstrumentRegion,
-    pub(crate) wave_data: View<i16>,
+    pub(crate) wave_data: WaveView,
 }
 
 impl Sound for RegionPair<'_> {
     fn get_wave_data(&self) -> View<i16> {
+        self.wave_data.to_view()
+    }
+
+    fn get_wave_view(&self) -> WaveView {
         self.wave_data.clone()
     }
     fn sample_sample_rate(&self) -> i32 {
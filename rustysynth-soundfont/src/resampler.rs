@@ -0,0 +1,288 @@
+use std::f64::consts::PI;
+
+use rustysynth::WaveData;
+
+use crate::generator_type::GeneratorType;
+use crate::soundfont::SoundFont;
+
+// The number of zero crossings on each side of the windowed sinc kernel.
+const ZERO_CROSSINGS: usize = 16;
+
+// The number of kernel values stored per zero crossing.
+const TABLE_RESOLUTION: usize = 256;
+
+// The Kaiser window parameter, which gives around 90 dB of stopband attenuation.
+const KAISER_BETA: f64 = 8.6;
+
+/// An offline windowed sinc resampler for the sample data.
+#[derive(Debug)]
+pub(crate) struct Resampler {
+    // The right half of the kernel, sampled at TABLE_RESOLUTION points per zero crossing.
+    table: Vec<f32>,
+}
+
+impl Resampler {
+    pub(crate) fn new() -> Self {
+        let length = ZERO_CROSSINGS * TABLE_RESOLUTION + 1;
+        let i0_beta = bessel_i0(KAISER_BETA);
+        let table = (0..length)
+            .map(|i| {
+                let x = i as f64 / TABLE_RESOLUTION as f64;
+                let r = x / ZERO_CROSSINGS as f64;
+                let window = bessel_i0(KAISER_BETA * (1.0 - r * r).max(0.0).sqrt()) / i0_beta;
+                (sinc(x) * window) as f32
+            })
+            .collect();
+
+        Self { table }
+    }
+
+    /// Resamples the data by the given ratio of the output rate to the input rate.
+    /// The data outside of the input is regarded as silence.
+    ///
+    /// # Arguments
+    ///
+    /// * `input` - The sample data to resample.
+    /// * `ratio` - The output sample rate divided by the input sample rate.
+    pub(crate) fn process(&self, input: &[i16], ratio: f64) -> Vec<i16> {
+        let output_length = (input.len() as f64 * ratio).ceil() as usize;
+        (0..output_length)
+            .map(|n| self.interpolate(n as f64 / ratio, ratio, |k| get_linear(input, k)))
+            .collect()
+    }
+
+    /// Resamples the data with a loop, so that the loop stays seamless.
+    /// The loop body is resampled as one period of an endless repetition,
+    /// and the part before the loop is followed by the loop as it is played.
+    /// The ratio has to make the loop length a whole number of output samples.
+    ///
+    /// # Arguments
+    ///
+    /// * `input` - The sample data to resample.
+    /// * `start_loop` - The start of the loop in the input.
+    /// * `end_loop` - The end of the loop in the input.
+    /// * `ratio` - The output sample rate divided by the input sample rate.
+    pub(crate) fn process_looped(
+        &self,
+        input: &[i16],
+        start_loop: usize,
+        end_loop: usize,
+        ratio: f64,
+    ) -> Vec<i16> {
+        let output_length = (input.len() as f64 * ratio).ceil() as usize;
+        let new_start_loop = scale(start_loop as i32, ratio) as usize;
+        let new_end_loop = new_start_loop + scale((end_loop - start_loop) as i32, ratio) as usize;
+
+        let get_cyclic = |k: i64| {
+            let length = (end_loop - start_loop) as i64;
+            input[start_loop + (k - start_loop as i64).rem_euclid(length) as usize]
+        };
+
+        (0..output_length)
+            .map(|n| {
+                let t = n as f64 / ratio;
+                if n < new_start_loop {
+                    self.interpolate(t, ratio, |k| {
+                        if k < end_loop as i64 {
+                            get_linear(input, k)
+                        } else {
+                            get_cyclic(k)
+                        }
+                    })
+                } else if n < new_end_loop {
+                    self.interpolate(t, ratio, get_cyclic)
+                } else {
+                    // The rest after the loop is played only after the note-off.
+                    self.interpolate(t, ratio, |k| get_linear(input, k))
+                }
+            })
+            .collect()
+    }
+
+    // Computes the output sample at the position t of the input.
+    fn interpolate<F: Fn(i64) -> i16>(&self, t: f64, ratio: f64, get: F) -> i16 {
+        // When downsampling, the cutoff frequency is lowered to avoid aliasing.
+        let cutoff = ratio.min(1.0);
+        let half_width = ZERO_CROSSINGS as f64 / cutoff;
+
+        let first = (t - half_width).ceil() as i64;
+        let last = (t + half_width).floor() as i64;
+        let mut sum = 0_f64;
+        for k in first..=last {
+            sum += get(k) as f64 * self.kernel(cutoff * (t - k as f64)) as f64;
+        }
+        (cutoff * sum).round().clamp(-32768.0, 32767.0) as i16
+    }
+
+    fn kernel(&self, x: f64) -> f32 {
+        let position = x.abs() * TABLE_RESOLUTION as f64;
+        let index = position as usize;
+        if index + 1 >= self.table.len() {
+            return 0.0;
+        }
+        let a = (position - index as f64) as f32;
+        self.table[index] + a * (self.table[index + 1] - self.table[index])
+    }
+}
+
+fn get_linear(input: &[i16], k: i64) -> i16 {
+    if 0 <= k && (k as usize) < input.len() {
+        input[k as usize]
+    } else {
+        0
+    }
+}
+
+fn sinc(x: f64) -> f64 {
+    if x == 0.0 {
+        1.0
+    } else {
+        (PI * x).sin() / (PI * x)
+    }
+}
+
+// The zeroth order modified Bessel function of the first kind.
+fn bessel_i0(x: f64) -> f64 {
+    let mut sum = 1.0;
+    let mut term = 1.0;
+    let half = x / 2.0;
+    for k in 1..50 {
+        term *= (half / k as f64) * (half / k as f64);
+        sum += term;
+        if term < 1.0e-12 * sum {
+            break;
+        }
+    }
+    sum
+}
+
+/// Resamples every sample of the SoundFont to the given sample rate.
+/// The sample headers and the instrument regions are updated to point to the new sample data.
+pub(crate) fn resample_sound_font(sound_font: &mut SoundFont, sample_rate: i32) {
+    let resampler = Resampler::new();
+
+    let source = std::mem::take(&mut sound_font.wave_data);
+    let mut wave_data: Vec<i16> = Vec::with_capacity(source.len());
+    let mut ratios: Vec<f64> = Vec::with_capacity(sound_font.sample_headers.len());
+    // The whole semitones of the pitch corrections, carried into either the root key or the fine tune.
+    let mut root_key_shifts: Vec<i32> = Vec::with_capacity(sound_font.sample_headers.len());
+    let mut fine_tunes: Vec<i32> = Vec::with_capacity(sound_font.sample_headers.len());
+
+    for header in &mut sound_font.sample_headers {
+        let end = (header.end.max(0) as usize).min(source.len());
+        let start = (header.start.max(0) as usize).min(end);
+
+        let mut ratio = if header.sample_rate > 0 && header.sample_rate != sample_rate {
+            sample_rate as f64 / header.sample_rate as f64
+        } else {
+            1.0
+        };
+
+        let start_loop = header.start_loop - start as i32;
+        let end_loop = header.end_loop - start as i32;
+        let has_loop = 0 <= start_loop && start_loop < end_loop && end_loop as usize <= end - start;
+
+        let mut root_key_shift = 0;
+        let mut fine_tune = 0;
+        let new_start = wave_data.len() as i32;
+        if ratio == 1.0 {
+            wave_data.extend_from_slice(&source.get_samples(start..end));
+        } else if has_loop {
+            // The loop length is rounded to a whole number of samples,
+            // and the small change of the ratio is corrected by the pitch.
+            let loop_length = end_loop - start_loop;
+            let new_loop_length = scale(loop_length, ratio).max(1);
+            let loop_ratio = new_loop_length as f64 / loop_length as f64;
+            // Whole semitones are carried into the root key, so the correction never overflows.
+            // The regions apply them by the fine tune if the sample has no root key to change.
+            let cents = header.pitch_correction as f64 + 1200.0 * (loop_ratio / ratio).log2();
+            let semitones = (cents / 100.0).round() as i32;
+            header.pitch_correction = (cents - 100.0 * semitones as f64).round() as i8;
+            match header.original_pitch as i32 - semitones {
+                key if header.original_pitch <= 127 && (0..=127).contains(&key) => {
+                    header.original_pitch = key as u8;
+                    root_key_shift = semitones;
+                }
+                _ => fine_tune = 100 * semitones,
+            }
+            ratio = loop_ratio;
+
+            wave_data.extend(resampler.process_looped(
+                &source.get_samples(start..end),
+                start_loop as usize,
+                end_loop as usize,
+                ratio,
+            ));
+            header.sample_rate = sample_rate;
+        } else {
+            wave_data.extend(resampler.process(&source.get_samples(start..end), ratio));
+            header.sample_rate = sample_rate;
+        }
+
+        let map = |position: i32| new_start + scale(position - start as i32, ratio);
+        if has_loop {
+            // Both ends are moved together to keep the loop length.
+            header.start_loop = map(header.start_loop);
+            header.end_loop = header.start_loop + scale(end_loop - start_loop, ratio);
+        } else {
+            header.start_loop = map(header.start_loop);
+            header.end_loop = map(header.end_loop);
+        }
+        header.start = new_start;
+        header.end = wave_data.len() as i32;
+
+        // Each sample is followed by at least 46 zero samples in the SoundFont format.
+        wave_data.extend_from_slice(&[0; 46]);
+
+        ratios.push(ratio);
+        root_key_shifts.push(root_key_shift);
+        fine_tunes.push(fine_tune);
+    }
+
+    for instrument in &mut sound_font.instruments {
+        for region in &mut instrument.regions {
+            let sample_id = region.get_sample_id();
+            region.set_sample(&sound_font.sample_headers[sample_id]);
+
+            // A region which overrides the root key does not follow the root key of the sample.
+            let mut fine_tune = fine_tunes[sample_id];
+            if region.gs[GeneratorType::OVERRIDING_ROOT_KEY as usize] != -1 {
+                fine_tune += 100 * root_key_shifts[sample_id];
+            }
+            region.gs[GeneratorType::FINE_TUNE as usize] += fine_tune as i16;
+
+            // The address offsets are in sample points, so they have to follow the new sample rate.
+            let ratio = ratios[sample_id];
+            for (fine, coarse) in [
+                (
+                    GeneratorType::START_ADDRESS_OFFSET,
+                    GeneratorType::START_ADDRESS_COARSE_OFFSET,
+                ),
+                (
+                    GeneratorType::END_ADDRESS_OFFSET,
+                    GeneratorType::END_ADDRESS_COARSE_OFFSET,
+                ),
+                (
+                    GeneratorType::START_LOOP_ADDRESS_OFFSET,
+                    GeneratorType::START_LOOP_ADDRESS_COARSE_OFFSET,
+                ),
+                (
+                    GeneratorType::END_LOOP_ADDRESS_OFFSET,
+                    GeneratorType::END_LOOP_ADDRESS_COARSE_OFFSET,
+                ),
+            ] {
+                let offset =
+                    32768 * region.gs[coarse as usize] as i32 + region.gs[fine as usize] as i32;
+                let offset = scale(offset, ratio);
+                region.gs[coarse as usize] = (offset / 32768) as i16;
+                region.gs[fine as usize] = (offset % 32768) as i16;
+            }
+        }
+    }
+
+    sound_font.set_wave_data(WaveData::from(wave_data));
+}
+
+fn scale(length: i32, ratio: f64) -> i32 {
+    (length as f64 * ratio).round() as i32
+}
@@ -0,0 +1,262 @@
+use std::borrow::Cow;
+use std::collections::HashSet;
+use std::fs::File;
+use std::io;
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+use rustysynth::PresetId;
+use rustysynth_wav::{SampleLoop, SamplerInfo, WavWriter};
+
+use crate::sample_header::SampleHeader;
+use crate::soundfont::SoundFont;
+
+const RIGHT_SAMPLE: u16 = 2;
+const LEFT_SAMPLE: u16 = 4;
+const ROM_SAMPLE: u16 = 0x8000;
+
+/// A WAV file to be written by `SampleExporter`.
+/// It contains a single sample, or a stereo-linked pair of samples.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SampleExport {
+    pub(crate) name: String,
+    pub(crate) samples: Vec<usize>,
+}
+
+impl SampleExport {
+    /// Gets the file name of the WAV file, without the extension.
+    pub fn get_name(&self) -> &str {
+        &self.name
+    }
+
+    /// Gets the indices of the samples written as the channels of the WAV file.
+    /// A stereo pair is ordered as left and right.
+    pub fn get_samples(&self) -> &[usize] {
+        &self.samples[..]
+    }
+}
+
+/// Exports the samples of a SoundFont to WAV files.
+/// The loop points, the original pitch and the pitch correction are stored in the 'smpl' chunk.
+#[derive(Debug)]
+pub struct SampleExporter<'a> {
+    sound_font: &'a SoundFont,
+    selected: Vec<bool>,
+}
+
+impl<'a> SampleExporter<'a> {
+    /// Creates an exporter which exports every sample of the SoundFont.
+    ///
+    /// # Arguments
+    ///
+    /// * `sound_font` - The SoundFont to export the samples from.
+    pub fn new(sound_font: &'a SoundFont) -> Self {
+        Self {
+            sound_font,
+            selected: vec![true; sound_font.sample_headers.len()],
+        }
+    }
+
+    /// Limits the export to the samples used by the given presets.
+    ///
+    /// # Arguments
+    ///
+    /// * `preset_ids` - The presets whose samples should be exported.
+    pub fn select_presets(&mut self, preset_ids: &[PresetId]) {
+        self.selected.fill(false);
+        for preset in &self.sound_font.presets {
+            if !preset
+                .get_preset_id()
+                .is_some_and(|id| preset_ids.contains(&id))
+            {
+                continue;
+            }
+            for preset_region in &preset.regions {
+                let instrument = &self.sound_font.instruments[preset_region.instrument];
+                for instrument_region in &instrument.regions {
+                    self.selected[instrument_region.get_sample_id()] = true;
+                }
+            }
+        }
+    }
+
+    /// Gets the WAV files to be written.
+    pub fn get_exports(&self) -> Vec<SampleExport> {
+        let headers = &self.sound_font.sample_headers;
+        let mut names: HashSet<String> = HashSet::new();
+        let mut exports: Vec<SampleExport> = Vec::new();
+
+        for (i, header) in headers.iter().enumerate() {
+            // ROM samples have no data in the file.
+            if header.sample_type & ROM_SAMPLE != 0 {
+                continue;
+            }
+
+            let samples = match find_stereo_pair(headers, i) {
+                Some((left, right)) if left == i => vec![left, right],
+                // The pair is exported with its left channel.
+                Some(_) => continue,
+                None => vec![i],
+            };
+
+            if !samples.iter().any(|&sample| self.selected[sample]) {
+                continue;
+            }
+
+            let name = match samples[..] {
+                [left, right] => get_pair_name(&headers[left].name, &headers[right].name),
+                _ => header.name.clone(),
+            };
+            let name = get_unique_name(&mut names, &sanitize_file_name(&name, i));
+
+            exports.push(SampleExport { name, samples });
+        }
+
+        exports
+    }
+
+    /// Writes a WAV file to the stream.
+    ///
+    /// # Arguments
+    ///
+    /// * `export` - The WAV file to write.
+    /// * `writer` - The data stream used to write the WAV file.
+    pub fn write<W: Write>(&self, export: &SampleExport, writer: &mut W) -> Result<(), io::Error> {
+        let headers = &self.sound_font.sample_headers;
+        let first = &headers[export.samples[0]];
+
+        let channels: Vec<Cow<[i16]>> = export
+            .samples
+            .iter()
+            .map(|&sample| self.get_sample_data(&headers[sample]))
+            .collect();
+
+        let mut wav = WavWriter::new(first.sample_rate.max(1) as u32);
+        for data in &channels {
+            wav.add_channel(data);
+        }
+        wav.set_sampler_info(create_sampler_info(first));
+        wav.write(writer)
+    }
+
+    /// Writes every WAV file to the directory.
+    /// Returns the paths of the written files.
+    ///
+    /// # Arguments
+    ///
+    /// * `directory` - The directory where the WAV files are written.
+    pub fn write_to_directory<P: AsRef<Path>>(
+        &self,
+        directory: P,
+    ) -> Result<Vec<PathBuf>, io::Error> {
+        let mut paths: Vec<PathBuf> = Vec::new();
+        for export in self.get_exports() {
+            let path = directory.as_ref().join(format!("{}.wav", export.name));
+            let mut writer = BufWriter::new(File::create(&path)?);
+            self.write(&export, &mut writer)?;
+            writer.flush()?;
+            paths.push(path);
+        }
+        Ok(paths)
+    }
+
+    fn get_sample_data(&self, header: &SampleHeader) -> Cow<'a, [i16]> {
+        let wave_data = &self.sound_font.wave_data;
+        let end = (header.end.max(0) as usize).min(wave_data.len());
+        let start = (header.start.max(0) as usize).min(end);
+        wave_data.get_samples(start..end)
+    }
+}
+
+// Returns the left and the right sample if the sample is a part of a valid stereo pair.
+fn find_stereo_pair(headers: &[SampleHeader], index: usize) -> Option<(usize, usize)> {
+    let header = &headers[index];
+    let link = header.link as usize;
+    let linked = headers.get(link)?;
+    if linked.link as usize != index || link == index {
+        return None;
+    }
+
+    if header.sample_type & LEFT_SAMPLE != 0 && linked.sample_type & RIGHT_SAMPLE != 0 {
+        Some((index, link))
+    } else if header.sample_type & RIGHT_SAMPLE != 0 && linked.sample_type & LEFT_SAMPLE != 0 {
+        Some((link, index))
+    } else {
+        None
+    }
+}
+
+fn create_sampler_info(header: &SampleHeader) -> SamplerInfo {
+    // The value 255 means that the sample is unpitched.
+    let mut unity_note = match header.original_pitch {
+        255 => 60,
+        value => value.min(127) as u32,
+    };
+
+    // The pitch correction is applied on playback, so the recorded pitch is off by the opposite amount.
+    // The pitch fraction can only express an offset upward from the unity note.
+    let mut cents = -(header.pitch_correction as i32);
+    if cents < 0 && unity_note > 0 {
+        unity_note -= 1;
+        cents += 100;
+    }
+    let pitch_fraction = (cents.max(0) as f64 / 100.0 * 4294967296.0).min(u32::MAX as f64) as u32;
+
+    // The loop points in the 'smpl' chunk are relative to the start of the data and the end is inclusive.
+    let mut loops: Vec<SampleLoop> = Vec::new();
+    if header.start <= header.start_loop
+        && header.start_loop < header.end_loop
+        && header.end_loop <= header.end
+    {
+        let start = (header.start_loop - header.start) as u32;
+        let end = (header.end_loop - header.start - 1) as u32;
+        loops.push(SampleLoop::new(0, start, end, 0));
+    }
+
+    SamplerInfo::new(unity_note, pitch_fraction, loops)
+}
+
+// Names such as "Piano L" and "Piano R" share the name "Piano".
+fn get_pair_name(left: &str, right: &str) -> String {
+    let length: usize = left
+        .chars()
+        .zip(right.chars())
+        .take_while(|(a, b)| a == b)
+        .map(|(a, _)| a.len_utf8())
+        .sum();
+    let name = left[..length].trim_end_matches([' ', '_', '-', '(', '[']);
+    if name.is_empty() {
+        left.to_string()
+    } else {
+        name.to_string()
+    }
+}
+
+fn sanitize_file_name(name: &str, index: usize) -> String {
+    let name: String = name
+        .chars()
+        .map(|c| {
+            if c.is_alphanumeric() || " -_()[]#+.".contains(c) {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    let name = name.trim_matches([' ', '.']);
+    if name.is_empty() {
+        format!("sample{}", index)
+    } else {
+        name.to_string()
+    }
+}
+
+fn get_unique_name(names: &mut HashSet<String>, name: &str) -> String {
+    let mut unique = name.to_string();
+    let mut count = 2;
+    while !names.insert(unique.to_lowercase()) {
+        unique = format!("{} ({})", name, count);
+        count += 1;
+    }
+    unique
+}
@@ -3,8 +3,12 @@ use std::io::Read;
 use crate::binary_reader::BinaryReader;
 use crate::error::SoundFontError;
 
+const MONO_SAMPLE: u16 = 1;
+const LINKED_SAMPLE_TYPES: u16 = 2 | 4 | 8;
+
 /// Represents a sample in the SoundFont.
 #[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct SampleHeader {
     pub(crate) name: String,
     pub(crate) start: i32,
@@ -49,7 +53,7 @@ impl SampleHeader {
         reader: &mut R,
         size: usize,
     ) -> Result<Vec<SampleHeader>, SoundFontError> {
-        if size == 0 || size % 46 != 0 {
+        if size == 0 || !size.is_multiple_of(46) {
             return Err(SoundFontError::InvalidSampleHeaderList);
         }
 
@@ -66,6 +70,26 @@ impl SampleHeader {
         Ok(headers)
     }
 
+    /// Returns `true` if the sample is linked to another sample, such as the other side of a stereo pair.
+    pub(crate) fn is_linked(&self) -> bool {
+        self.sample_type & LINKED_SAMPLE_TYPES != 0
+    }
+
+    /// Makes the sample mono, for when the linked sample is not kept.
+    pub(crate) fn unlink(&mut self) {
+        self.link = 0;
+        self.sample_type = self.get_unlinked_sample_type();
+    }
+
+    /// Gets the type which the sample has after `unlink`.
+    pub(crate) fn get_unlinked_sample_type(&self) -> u16 {
+        if self.is_linked() {
+            (self.sample_type & !LINKED_SAMPLE_TYPES) | MONO_SAMPLE
+        } else {
+            self.sample_type
+        }
+    }
+
     /// Gets the name of the sample.
     pub fn get_name(&self) -> &str {
         &self.name
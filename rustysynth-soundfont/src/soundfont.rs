@@ -1,21 +1,37 @@
-use std::io::Read;
+use std::io;
+use std::io::{Cursor, Read, Seek, SeekFrom, Write};
+use std::ops::Range;
+use std::sync::{Arc, OnceLock};
+
+use rustysynth::WaveData;
 
 use crate::binary_reader::BinaryReader;
 use crate::error::SoundFontError;
 use crate::four_cc::FourCC;
 use crate::instrument::Instrument;
+use crate::partial_load;
 use crate::preset::Preset;
+use crate::preset_region;
+use crate::preset_selector::PresetSelector;
 use crate::sample_header::SampleHeader;
 use crate::soundfont_info::SoundFontInfo;
-use crate::soundfont_parameters::SoundFontParameters;
+use crate::soundfont_parameters::{ParameterLists, SoundFontParameters};
 use crate::soundfont_sampledata::SoundFontSampleData;
+use crate::soundfont_writer;
 
 /// Reperesents a SoundFont.
+/// When serialized, the sample data is omitted and the samples are referenced by their offsets.
+/// A deserialized SoundFont becomes playable after the sample data is restored by `rebuild`.
 #[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct SoundFont {
     pub(crate) info: SoundFontInfo,
     pub(crate) bits_per_sample: i32,
-    pub(crate) wave_data: Vec<i16>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub(crate) wave_data: WaveData,
+    // The samples decoded by `get_wave_data` when the sample data is kept as bytes.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub(crate) decoded_wave_data: OnceLock<Vec<i16>>,
     pub(crate) sample_headers: Vec<SampleHeader>,
     pub(crate) presets: Vec<Preset>,
     pub(crate) instruments: Vec<Instrument>,
@@ -28,12 +44,24 @@ impl SoundFont {
     ///
     /// * `reader` - The data stream used to load the SoundFont.
     pub fn new<R: Read>(reader: &mut R) -> Result<Self, SoundFontError> {
+        SoundFont::read_riff_header(reader)?;
+
+        let info = SoundFontInfo::new(reader)?;
+        let sample_data = SoundFontSampleData::new(reader)?;
+        let parameters = SoundFontParameters::new(reader)?;
+
+        Self::create(info, WaveData::from(sample_data.wave_data), parameters)
+    }
+
+    /// Reads the header of the RIFF chunk.
+    /// Returns the size of the RIFF chunk.
+    pub(crate) fn read_riff_header<R: Read>(reader: &mut R) -> Result<u32, SoundFontError> {
         let chunk_id = BinaryReader::read_four_cc(reader)?;
         if chunk_id != b"RIFF" {
             return Err(SoundFontError::RiffChunkNotFound);
         }
 
-        let _size = BinaryReader::read_i32(reader);
+        let size = BinaryReader::read_u32(reader)?;
 
         let form_type = BinaryReader::read_four_cc(reader)?;
         if form_type != b"sfbk" {
@@ -43,14 +71,127 @@ impl SoundFont {
             });
         }
 
+        Ok(size)
+    }
+
+    /// Loads a SoundFont from the seekable stream.
+    /// Unlike `new`, the chunks are located by their sizes, so they may appear in any order.
+    ///
+    /// # Arguments
+    ///
+    /// * `reader` - The data stream used to load the SoundFont.
+    pub fn from_seekable<R: Read + Seek>(reader: &mut R) -> Result<Self, SoundFontError> {
+        let chunks = ListChunks::find(reader)?;
+
+        reader.seek(SeekFrom::Start(chunks.info))?;
         let info = SoundFontInfo::new(reader)?;
+        reader.seek(SeekFrom::Start(chunks.sample_data))?;
         let sample_data = SoundFontSampleData::new(reader)?;
+        reader.seek(SeekFrom::Start(chunks.parameters))?;
+        let parameters = SoundFontParameters::new(reader)?;
+
+        Self::create(info, WaveData::from(sample_data.wave_data), parameters)
+    }
+
+    /// Loads only the selected presets from the seekable stream.
+    /// Only the instruments used by the presets are built, and only the sample data they use is read,
+    /// so that the memory use is limited to what is actually playable.
+    ///
+    /// # Arguments
+    ///
+    /// * `reader` - The data stream used to load the SoundFont.
+    /// * `presets` - The presets to load. Every selector must match at least one preset.
+    pub fn from_seekable_partial<R: Read + Seek>(
+        reader: &mut R,
+        presets: &[PresetSelector],
+    ) -> Result<Self, SoundFontError> {
+        let chunks = ListChunks::find(reader)?;
+
+        reader.seek(SeekFrom::Start(chunks.info))?;
+        let info = SoundFontInfo::new(reader)?;
+        reader.seek(SeekFrom::Start(chunks.sample_data))?;
+        let range = SoundFontSampleData::locate(reader)?;
+        reader.seek(SeekFrom::Start(chunks.parameters))?;
+        let lists = ParameterLists::new(reader)?;
+        let parameters = SoundFontParameters::build_selected(lists, presets)?;
+
+        let mut sound_font = Self {
+            info,
+            bits_per_sample: 16,
+            wave_data: WaveData::default(),
+            decoded_wave_data: OnceLock::new(),
+            sample_headers: parameters.sample_headers,
+            presets: parameters.presets,
+            instruments: parameters.instruments,
+        };
+
+        partial_load::compact_samples(&mut sound_font, range.len() / 2, |samples| {
+            reader.seek(SeekFrom::Start((range.start + 2 * samples.start) as u64))?;
+            Ok(BinaryReader::read_wave_data(reader, 2 * samples.len())?)
+        })?;
+
+        sound_font.sanity_check()?;
+
+        Ok(sound_font)
+    }
+
+    /// Loads a SoundFont from the static data, such as the one embedded by `include_bytes!`.
+    /// The sample data is referenced in place without copying.
+    ///
+    /// # Arguments
+    ///
+    /// * `data` - The content of the SoundFont file.
+    pub fn from_static(data: &'static [u8]) -> Result<Self, SoundFontError> {
+        let (info, range, parameters) = SoundFont::load_in_place(data)?;
+        Self::create(info, WaveData::Static(&data[range]), parameters)
+    }
+
+    /// Loads a SoundFont from the shared data.
+    /// The sample data is referenced in place without copying.
+    ///
+    /// # Arguments
+    ///
+    /// * `data` - The content of the SoundFont file.
+    pub fn from_bytes(data: Arc<[u8]>) -> Result<Self, SoundFontError> {
+        let (info, range, parameters) = SoundFont::load_in_place(&data)?;
+        let wave_data = WaveData::Bytes {
+            offset: range.start,
+            len: range.len() / 2,
+            data,
+        };
+        Self::create(info, wave_data, parameters)
+    }
+
+    fn load_in_place(
+        data: &[u8],
+    ) -> Result<(SoundFontInfo, Range<usize>, SoundFontParameters), SoundFontError> {
+        let reader = &mut Cursor::new(data);
+        let chunks = ListChunks::find(reader)?;
+
+        reader.seek(SeekFrom::Start(chunks.info))?;
+        let info = SoundFontInfo::new(reader)?;
+        reader.seek(SeekFrom::Start(chunks.sample_data))?;
+        let range = SoundFontSampleData::locate(reader)?;
+        reader.seek(SeekFrom::Start(chunks.parameters))?;
         let parameters = SoundFontParameters::new(reader)?;
 
+        if range.end > data.len() {
+            return Err(io::Error::from(io::ErrorKind::UnexpectedEof).into());
+        }
+
+        Ok((info, range, parameters))
+    }
+
+    pub(crate) fn create(
+        info: SoundFontInfo,
+        wave_data: WaveData,
+        parameters: SoundFontParameters,
+    ) -> Result<Self, SoundFontError> {
         let sound_font = Self {
             info,
             bits_per_sample: 16,
-            wave_data: sample_data.wave_data,
+            wave_data,
+            decoded_wave_data: OnceLock::new(),
             sample_headers: parameters.sample_headers,
             presets: parameters.presets,
             instruments: parameters.instruments,
@@ -61,20 +202,108 @@ impl SoundFont {
         Ok(sound_font)
     }
 
+    /// Restores the sample data and the references between the parts of the SoundFont.
+    /// This is needed after the SoundFont is deserialized or edited.
+    ///
+    /// # Arguments
+    ///
+    /// * `wave_data` - The sample data, usually taken from the original SoundFont.
+    ///   Either decoded samples or a `WaveData` can be given.
+    pub fn rebuild(&mut self, wave_data: impl Into<WaveData>) -> Result<(), SoundFontError> {
+        for (preset_id, preset) in self.presets.iter_mut().enumerate() {
+            for region in &mut preset.regions {
+                let instrument_id = preset_region::get_instrument_id(&region.gs);
+                if instrument_id >= self.instruments.len() {
+                    return Err(SoundFontError::InvalidInstrumentId {
+                        preset_id,
+                        instrument_id,
+                    });
+                }
+                region.instrument = instrument_id;
+            }
+        }
+
+        for (instrument_id, instrument) in self.instruments.iter_mut().enumerate() {
+            for region in &mut instrument.regions {
+                let sample_id = region.get_sample_id();
+                match self.sample_headers.get(sample_id) {
+                    Some(sample) => region.set_sample(sample),
+                    None => {
+                        return Err(SoundFontError::InvalidSampleId {
+                            instrument_id,
+                            sample_id,
+                        });
+                    }
+                }
+            }
+        }
+
+        self.set_wave_data(wave_data.into());
+
+        self.sanity_check()
+    }
+
+    /// Removes every preset except the selected ones, along with the instruments and the samples they do not use.
+    /// The remaining sample data is compacted into a new buffer.
+    ///
+    /// # Arguments
+    ///
+    /// * `presets` - The presets to keep. Every selector must match at least one preset.
+    pub fn retain_presets(&mut self, presets: &[PresetSelector]) -> Result<(), SoundFontError> {
+        // The SoundFont is left unchanged if any preset is missing.
+        partial_load::check_selectors(&self.presets, presets)?;
+
+        let (selected, instrument_ids) =
+            partial_load::select_presets(std::mem::take(&mut self.presets), presets)?;
+
+        let mut instruments: Vec<Option<Instrument>> = std::mem::take(&mut self.instruments)
+            .into_iter()
+            .map(Some)
+            .collect();
+        self.presets = selected;
+        self.instruments = instrument_ids
+            .iter()
+            .filter_map(|id| instruments[*id].take())
+            .collect();
+
+        let wave_data = std::mem::take(&mut self.wave_data);
+        partial_load::compact_samples(self, wave_data.len(), |samples| {
+            Ok(wave_data.get_samples(samples).into_owned())
+        })?;
+
+        self.sanity_check()
+    }
+
     fn sanity_check(&self) -> Result<(), SoundFontError> {
+        // The positions are handled as i32 during playback.
+        let length = self.wave_data.len() as i64;
+        if length > i32::MAX as i64 {
+            return Err(SoundFontError::SanityCheckFailed);
+        }
+
         // https://github.com/sinshu/rustysynth/issues/22
         // https://github.com/sinshu/rustysynth/issues/33
         for instrument in &self.instruments {
             for region in &instrument.regions {
-                let start = region.get_sample_start();
-                let end = region.get_sample_end();
-                let start_loop = region.get_sample_start_loop();
-                let end_loop = region.get_sample_end_loop();
+                // The sample itself is played from the start to the end without the offsets.
+                let sample_start = region.sample_start as i64;
+                let sample_end = region.sample_end as i64;
+                if sample_start < 0 || sample_end > length || sample_end < sample_start {
+                    return Err(SoundFontError::SanityCheckFailed);
+                }
+
+                // The sums are computed in i64, as hostile values could overflow i32.
+                let start = sample_start + region.get_start_address_offset() as i64;
+                let end = sample_end + region.get_end_address_offset() as i64;
+                let start_loop =
+                    region.sample_start_loop as i64 + region.get_start_loop_address_offset() as i64;
+                let end_loop =
+                    region.sample_end_loop as i64 + region.get_end_loop_address_offset() as i64;
 
                 if start < 0
                     || start_loop < 0
-                    || end as usize >= self.wave_data.len()
-                    || end_loop as usize >= self.wave_data.len()
+                    || end >= length
+                    || end_loop >= length
                     || end <= start
                     || end_loop < start_loop
                 {
@@ -86,6 +315,16 @@ impl SoundFont {
         Ok(())
     }
 
+    /// Writes the SoundFont to the stream in the SF2 format.
+    /// The regions are written as they are resolved, so the global zones and the modulators are not kept.
+    ///
+    /// # Arguments
+    ///
+    /// * `writer` - The data stream used to write the SoundFont.
+    pub fn write<W: Write>(&self, writer: &mut W) -> Result<(), io::Error> {
+        soundfont_writer::write(self, writer)
+    }
+
     /// Gets the information of the SoundFont.
     pub fn get_info(&self) -> &SoundFontInfo {
         &self.info
@@ -96,9 +335,28 @@ impl SoundFont {
         self.bits_per_sample
     }
 
-    /// Gets the sample data.
+    /// Gets the sample data as decoded samples.
+    /// If the sample data is kept as bytes, such as for a SoundFont created by `from_bytes`,
+    /// the samples are decoded on the first call and kept with the SoundFont.
+    /// Use `get_wave_storage` to access the sample data without decoding.
     pub fn get_wave_data(&self) -> &[i16] {
-        &self.wave_data[..]
+        match &self.wave_data {
+            WaveData::Samples(data) => data,
+            data => self
+                .decoded_wave_data
+                .get_or_init(|| data.get_samples(0..data.len()).into_owned()),
+        }
+    }
+
+    /// Gets the sample data as it is stored, either decoded or as bytes referenced in place.
+    pub fn get_wave_storage(&self) -> &WaveData {
+        &self.wave_data
+    }
+
+    // Replaces the sample data, and discards the samples decoded from the previous one.
+    pub(crate) fn set_wave_data(&mut self, wave_data: WaveData) {
+        self.wave_data = wave_data;
+        self.decoded_wave_data = OnceLock::new();
     }
 
     /// Gets the samples of the SoundFont.
@@ -116,3 +374,71 @@ impl SoundFont {
         &self.instruments[..]
     }
 }
+
+// The positions of the top-level LIST chunks.
+struct ListChunks {
+    info: u64,
+    sample_data: u64,
+    parameters: u64,
+}
+
+impl ListChunks {
+    fn find<R: Read + Seek>(reader: &mut R) -> Result<Self, SoundFontError> {
+        let chunk_id = BinaryReader::read_four_cc(reader)?;
+        if chunk_id != b"RIFF" {
+            return Err(SoundFontError::RiffChunkNotFound);
+        }
+
+        let size = BinaryReader::read_u32(reader)? as u64;
+        let end = reader.stream_position()? + size;
+
+        let form_type = BinaryReader::read_four_cc(reader)?;
+        if form_type != b"sfbk" {
+            return Err(SoundFontError::InvalidRiffChunkType {
+                expected: FourCC::from_bytes(*b"sfbk"),
+                actual: form_type,
+            });
+        }
+
+        let mut info: Option<u64> = None;
+        let mut sample_data: Option<u64> = None;
+        let mut parameters: Option<u64> = None;
+
+        let mut position = reader.stream_position()?;
+        while position < end {
+            let id = match BinaryReader::read_four_cc(reader) {
+                Ok(value) => value,
+                // Some files have a wrong size in the RIFF header.
+                Err(err) if err.kind() == io::ErrorKind::UnexpectedEof => break,
+                Err(err) => return Err(err.into()),
+            };
+            let size = BinaryReader::read_u32(reader)? as u64;
+
+            if id == b"LIST" {
+                let list_type = BinaryReader::read_four_cc(reader)?;
+                match list_type.as_bytes() {
+                    b"INFO" => info = Some(position),
+                    b"sdta" => sample_data = Some(position),
+                    b"pdta" => parameters = Some(position),
+                    _ => (),
+                }
+            }
+
+            // Chunks are padded to an even size.
+            position += 8 + size + size % 2;
+            reader.seek(SeekFrom::Start(position))?;
+        }
+
+        Ok(Self {
+            info: info.ok_or(SoundFontError::SubChunkNotFound(FourCC::from_bytes(
+                *b"INFO",
+            )))?,
+            sample_data: sample_data.ok_or(SoundFontError::SubChunkNotFound(
+                FourCC::from_bytes(*b"sdta"),
+            ))?,
+            parameters: parameters.ok_or(SoundFontError::SubChunkNotFound(FourCC::from_bytes(
+                *b"pdta",
+            )))?,
+        })
+    }
+}
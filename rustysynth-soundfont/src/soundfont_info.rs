@@ -6,8 +6,12 @@ use crate::four_cc::FourCC;
 use crate::read_counter::ReadCounter;
 use crate::soundfont_version::SoundFontVersion;
 
+// The comments may have up to 65536 bytes, which is the longest string allowed in the INFO list.
+const MAX_STRING_SIZE: usize = 65536;
+
 /// The information of a SoundFont.
 #[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct SoundFontInfo {
     pub(crate) version: SoundFontVersion,
     pub(crate) target_sound_engine: String,
@@ -56,6 +60,14 @@ impl SoundFontInfo {
             let id = BinaryReader::read_four_cc(reader)?;
             let size = BinaryReader::read_u32(reader)? as usize;
 
+            let valid_size = match id.as_bytes() {
+                b"ifil" | b"iver" => size == 4,
+                _ => size <= MAX_STRING_SIZE,
+            };
+            if !valid_size {
+                return Err(SoundFontError::InvalidChunkSize(id));
+            }
+
             match id.as_bytes() {
                 b"ifil" => version = Some(SoundFontVersion::new(reader)?),
                 b"isng" => {
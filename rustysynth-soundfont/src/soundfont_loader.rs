@@ -0,0 +1,147 @@
+use std::cell::Cell;
+use std::io;
+use std::io::Read;
+use std::ops::ControlFlow;
+
+use rustysynth::WaveData;
+
+use crate::error::SoundFontError;
+use crate::read_counter::ReadCounter;
+use crate::soundfont::SoundFont;
+use crate::soundfont_info::SoundFontInfo;
+use crate::soundfont_parameters::{ParameterLists, SoundFontParameters};
+use crate::soundfont_sampledata::SoundFontSampleData;
+
+// The progress is reported each time this many bytes have been read.
+const REPORT_INTERVAL: usize = 256 * 1024;
+
+/// Specifies the part of the SoundFont being loaded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LoadPhase {
+    /// Reading the INFO list.
+    Info,
+    /// Reading the sample data.
+    SampleData,
+    /// Reading the preset, instrument and sample header lists.
+    Parameters,
+    /// Building the regions from the lists. No data is read in this phase.
+    Regions,
+}
+
+/// The progress of loading a SoundFont, reported by `SoundFontLoader`.
+#[derive(Debug, Clone, Copy)]
+pub struct LoadProgress {
+    pub(crate) phase: LoadPhase,
+    pub(crate) bytes_read: u64,
+    pub(crate) total_bytes: u64,
+}
+
+impl LoadProgress {
+    /// Gets the part of the SoundFont being loaded.
+    pub fn get_phase(&self) -> LoadPhase {
+        self.phase
+    }
+
+    /// Gets the number of bytes read so far.
+    pub fn get_bytes_read(&self) -> u64 {
+        self.bytes_read
+    }
+
+    /// Gets the size of the file declared in the RIFF header.
+    /// The value may be inaccurate for broken files.
+    pub fn get_total_bytes(&self) -> u64 {
+        self.total_bytes
+    }
+}
+
+/// Loads a SoundFont while reporting the progress.
+/// The callback is called at the start of each phase and periodically while the data is read.
+/// If the callback returns `ControlFlow::Break`, loading stops with `SoundFontError::Cancelled`.
+pub struct SoundFontLoader<F: FnMut(&LoadProgress) -> ControlFlow<()>> {
+    callback: F,
+}
+
+impl<F: FnMut(&LoadProgress) -> ControlFlow<()>> SoundFontLoader<F> {
+    /// Creates a new loader.
+    ///
+    /// # Arguments
+    ///
+    /// * `callback` - The function which receives the progress and decides whether to continue.
+    pub fn new(callback: F) -> Self {
+        Self { callback }
+    }
+
+    /// Loads a SoundFont from the stream.
+    ///
+    /// # Arguments
+    ///
+    /// * `reader` - The data stream used to load the SoundFont.
+    pub fn load<R: Read>(&mut self, reader: &mut R) -> Result<SoundFont, SoundFontError> {
+        let state = LoadState {
+            phase: Cell::new(LoadPhase::Info),
+            total_bytes: Cell::new(0),
+            cancelled: Cell::new(false),
+        };
+        let mut report = |bytes_read: usize| {
+            let progress = LoadProgress {
+                phase: state.phase.get(),
+                bytes_read: bytes_read as u64,
+                total_bytes: state.total_bytes.get(),
+            };
+            if (self.callback)(&progress).is_break() {
+                state.cancelled.set(true);
+                return Err(io::Error::other("loading was cancelled"));
+            }
+            Ok(())
+        };
+        let mut reader = ReadCounter::with_progress(reader, REPORT_INTERVAL, &mut report);
+
+        match load_phases(&mut reader, &state) {
+            // The error from the reader is replaced with the one that tells the reason.
+            Err(_) if state.cancelled.get() => Err(SoundFontError::Cancelled),
+            result => result,
+        }
+    }
+}
+
+// The state shared by the loader and the progress callback of the reader.
+struct LoadState {
+    phase: Cell<LoadPhase>,
+    total_bytes: Cell<u64>,
+    cancelled: Cell<bool>,
+}
+
+impl LoadState {
+    fn start_phase<R: Read>(
+        &self,
+        reader: &mut ReadCounter<R>,
+        phase: LoadPhase,
+    ) -> Result<(), SoundFontError> {
+        self.phase.set(phase);
+        reader
+            .report_progress()
+            .map_err(|_| SoundFontError::Cancelled)
+    }
+}
+
+fn load_phases<R: Read>(
+    reader: &mut ReadCounter<R>,
+    state: &LoadState,
+) -> Result<SoundFont, SoundFontError> {
+    let size = SoundFont::read_riff_header(reader)?;
+    state.total_bytes.set(8 + size as u64);
+
+    state.start_phase(reader, LoadPhase::Info)?;
+    let info = SoundFontInfo::new(reader)?;
+
+    state.start_phase(reader, LoadPhase::SampleData)?;
+    let sample_data = SoundFontSampleData::new(reader)?;
+
+    state.start_phase(reader, LoadPhase::Parameters)?;
+    let lists = ParameterLists::new(reader)?;
+
+    state.start_phase(reader, LoadPhase::Regions)?;
+    let parameters = SoundFontParameters::build(lists)?;
+
+    SoundFont::create(info, WaveData::from(sample_data.wave_data), parameters)
+}
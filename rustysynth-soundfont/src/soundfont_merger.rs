@@ -0,0 +1,333 @@
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::ops::Range;
+use std::sync::OnceLock;
+
+use rustysynth::{PresetId, WaveData};
+
+use crate::error::SoundFontError;
+use crate::generator_type::GeneratorType;
+use crate::instrument::Instrument;
+use crate::instrument_region::InstrumentRegion;
+use crate::preset::Preset;
+use crate::preset_region::PresetRegion;
+use crate::sample_header::SampleHeader;
+use crate::soundfont::SoundFont;
+use crate::soundfont_info::SoundFontInfo;
+use crate::soundfont_version::SoundFontVersion;
+
+/// Builds a new SoundFont from presets taken out of other SoundFonts.
+/// Only the instruments and the samples used by the presets are copied,
+/// and identical samples are stored once.
+#[derive(Debug, Default)]
+pub struct SoundFontMerger<'a> {
+    bank_name: String,
+    entries: Vec<MergeEntry<'a>>,
+}
+
+#[derive(Debug)]
+struct MergeEntry<'a> {
+    sound_font: &'a SoundFont,
+    preset: usize,
+    preset_id: PresetId,
+}
+
+impl<'a> SoundFontMerger<'a> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the name of the new bank.
+    pub fn set_bank_name(&mut self, bank_name: &str) {
+        self.bank_name = bank_name.to_string();
+    }
+
+    /// Adds a preset to the new bank.
+    ///
+    /// # Arguments
+    ///
+    /// * `sound_font` - The SoundFont which has the preset.
+    /// * `source_id` - The ID of the preset in the source SoundFont.
+    ///   If the SoundFont has more than one preset with the ID, the last one is used, as in playback.
+    /// * `preset_id` - The ID of the preset in the new bank, which must not be used by another added preset.
+    pub fn add_preset(
+        &mut self,
+        sound_font: &'a SoundFont,
+        source_id: PresetId,
+        preset_id: PresetId,
+    ) -> Result<(), SoundFontError> {
+        if self
+            .entries
+            .iter()
+            .any(|entry| entry.preset_id == preset_id)
+        {
+            return Err(SoundFontError::DuplicatePreset(preset_id));
+        }
+
+        let preset = sound_font
+            .presets
+            .iter()
+            .rposition(|preset| preset.get_preset_id() == Some(source_id))
+            .ok_or(SoundFontError::UnknownPreset(source_id))?;
+
+        self.entries.push(MergeEntry {
+            sound_font,
+            preset,
+            preset_id,
+        });
+
+        Ok(())
+    }
+
+    /// Creates the new bank from the added presets.
+    pub fn merge(&self) -> Result<SoundFont, SoundFontError> {
+        let mut state = MergeState::default();
+
+        let mut presets: Vec<Preset> = Vec::new();
+        for entry in &self.entries {
+            let source = state.get_source_index(entry.sound_font);
+            let preset = &entry.sound_font.presets[entry.preset];
+
+            let mut regions: Vec<PresetRegion> = Vec::new();
+            for region in &preset.regions {
+                let instrument = state.copy_instrument(entry.sound_font, source, region.instrument);
+                let mut gs = region.gs;
+                gs[GeneratorType::INSTRUMENT as usize] = instrument as i16;
+                regions.push(PresetRegion { gs, instrument });
+            }
+
+            presets.push(Preset {
+                name: preset.name.clone(),
+                patch_number: entry.preset_id.program as i32,
+                bank_number: entry.preset_id.bank as i32,
+                library: preset.library,
+                genre: preset.genre,
+                morphology: preset.morphology,
+                regions,
+            });
+        }
+
+        let info = SoundFontInfo {
+            version: SoundFontVersion { major: 2, minor: 1 },
+            target_sound_engine: "EMU8000".to_string(),
+            bank_name: self.bank_name.clone(),
+            rom_name: String::new(),
+            rom_version: SoundFontVersion::default(),
+            creation_date: String::new(),
+            author: String::new(),
+            target_product: String::new(),
+            copyright: String::new(),
+            comments: String::new(),
+            tools: String::new(),
+        };
+
+        let mut sound_font = SoundFont {
+            info,
+            bits_per_sample: 16,
+            wave_data: WaveData::default(),
+            decoded_wave_data: OnceLock::new(),
+            sample_headers: state.sample_headers,
+            presets,
+            instruments: state.instruments,
+        };
+        sound_font.rebuild(state.wave_data)?;
+
+        Ok(sound_font)
+    }
+}
+
+// Samples with the same content and parameters have the same key.
+// The key keeps a hash of the content instead of the content itself,
+// so a match is confirmed by comparing the samples.
+#[derive(Clone, PartialEq, Eq, Hash)]
+struct SampleKey {
+    hash: u64,
+    len: usize,
+    start_loop: i32,
+    end_loop: i32,
+    sample_rate: i32,
+    original_pitch: u8,
+    pitch_correction: i8,
+    sample_type: u16,
+}
+
+impl SampleKey {
+    fn new(header: &SampleHeader, start: usize, data: &[i16], sample_type: u16) -> Self {
+        let mut hasher = DefaultHasher::new();
+        data.hash(&mut hasher);
+
+        Self {
+            hash: hasher.finish(),
+            len: data.len(),
+            start_loop: header.start_loop - start as i32,
+            end_loop: header.end_loop - start as i32,
+            sample_rate: header.sample_rate,
+            original_pitch: header.original_pitch,
+            pitch_correction: header.pitch_correction,
+            sample_type,
+        }
+    }
+}
+
+#[derive(Default)]
+struct MergeState<'a> {
+    sources: Vec<&'a SoundFont>,
+    wave_data: Vec<i16>,
+    sample_headers: Vec<SampleHeader>,
+    instruments: Vec<Instrument>,
+    sample_map: HashMap<(usize, usize), usize>,
+    // A stereo pair is stored under the keys of both samples, so that it is shared as a unit.
+    // Different samples can have the same key, so each key has a list of the copies.
+    sample_keys: HashMap<Vec<SampleKey>, Vec<Vec<usize>>>,
+    instrument_map: HashMap<(usize, usize), usize>,
+}
+
+impl<'a> MergeState<'a> {
+    fn get_source_index(&mut self, sound_font: &'a SoundFont) -> usize {
+        match self
+            .sources
+            .iter()
+            .position(|source| std::ptr::eq(*source, sound_font))
+        {
+            Some(index) => index,
+            None => {
+                self.sources.push(sound_font);
+                self.sources.len() - 1
+            }
+        }
+    }
+
+    fn copy_instrument(&mut self, sound_font: &SoundFont, source: usize, index: usize) -> usize {
+        if let Some(copied) = self.instrument_map.get(&(source, index)) {
+            return *copied;
+        }
+
+        let instrument = &sound_font.instruments[index];
+        let mut regions: Vec<InstrumentRegion> = Vec::new();
+        for region in &instrument.regions {
+            let sample = self.copy_sample(sound_font, source, region.get_sample_id());
+            let mut gs = region.gs;
+            gs[GeneratorType::SAMPLE_ID as usize] = sample as i16;
+            // The parameters of the sample are copied by `SoundFont::rebuild`.
+            regions.push(InstrumentRegion {
+                gs,
+                sample_start: 0,
+                sample_end: 0,
+                sample_start_loop: 0,
+                sample_end_loop: 0,
+                sample_sample_rate: 0,
+                sample_original_pitch: 0,
+                sample_pitch_correction: 0,
+            });
+        }
+
+        self.instruments.push(Instrument {
+            name: instrument.name.clone(),
+            regions,
+        });
+        let copied = self.instruments.len() - 1;
+        self.instrument_map.insert((source, index), copied);
+        copied
+    }
+
+    // A stereo-linked sample is copied together with the linked sample.
+    // If the linked sample does not exist, the sample is made mono.
+    fn copy_sample(&mut self, sound_font: &SoundFont, source: usize, index: usize) -> usize {
+        if let Some(copied) = self.sample_map.get(&(source, index)) {
+            return *copied;
+        }
+
+        let header = &sound_font.sample_headers[index];
+        let link = header.link as usize;
+        let indices =
+            if header.is_linked() && link != index && link < sound_font.sample_headers.len() {
+                vec![index, link]
+            } else {
+                vec![index]
+            };
+
+        let copied = self.copy_samples(sound_font, &indices);
+        for (index, copied) in indices.iter().zip(&copied) {
+            self.sample_map.insert((source, *index), *copied);
+        }
+        copied[0]
+    }
+
+    fn copy_samples(&mut self, sound_font: &SoundFont, indices: &[usize]) -> Vec<usize> {
+        let linked = indices.len() > 1;
+        let samples: Vec<(Range<usize>, Cow<'_, [i16]>)> = indices
+            .iter()
+            .map(|index| {
+                let header = &sound_font.sample_headers[*index];
+                let end = (header.end.max(0) as usize).min(sound_font.wave_data.len());
+                let start = (header.start.max(0) as usize).min(end);
+                (start..end, sound_font.wave_data.get_samples(start..end))
+            })
+            .collect();
+        let keys: Vec<SampleKey> = indices
+            .iter()
+            .zip(&samples)
+            .map(|(index, (range, data))| {
+                let header = &sound_font.sample_headers[*index];
+                let sample_type = if linked {
+                    header.sample_type
+                } else {
+                    header.get_unlinked_sample_type()
+                };
+                SampleKey::new(header, range.start, data, sample_type)
+            })
+            .collect();
+
+        if let Some(candidates) = self.sample_keys.get(&keys) {
+            let found = candidates.iter().find(|copied| {
+                copied.iter().zip(&samples).all(|(copied, (_, data))| {
+                    let header = &self.sample_headers[*copied];
+                    self.wave_data[header.start as usize..header.end as usize] == **data
+                })
+            });
+            if let Some(copied) = found {
+                return copied.clone();
+            }
+        }
+
+        let mut copied: Vec<usize> = Vec::new();
+        for ((index, (_, data)), key) in indices.iter().zip(&samples).zip(&keys) {
+            let header = &sound_font.sample_headers[*index];
+            let new_start = self.wave_data.len() as i32;
+            self.wave_data.extend_from_slice(data);
+            let new_end = self.wave_data.len() as i32;
+            // Each sample is followed by at least 46 zero samples in the SoundFont format.
+            self.wave_data.extend_from_slice(&[0; 46]);
+
+            self.sample_headers.push(SampleHeader {
+                name: header.name.clone(),
+                start: new_start,
+                end: new_end,
+                start_loop: new_start + key.start_loop,
+                end_loop: new_start + key.end_loop,
+                sample_rate: header.sample_rate,
+                original_pitch: header.original_pitch,
+                pitch_correction: header.pitch_correction,
+                link: 0,
+                sample_type: key.sample_type,
+            });
+            copied.push(self.sample_headers.len() - 1);
+        }
+
+        if linked {
+            self.sample_headers[copied[0]].link = copied[1] as u16;
+            self.sample_headers[copied[1]].link = copied[0] as u16;
+            let reversed_keys = vec![keys[1].clone(), keys[0].clone()];
+            self.sample_keys
+                .entry(reversed_keys)
+                .or_default()
+                .push(vec![copied[1], copied[0]]);
+        }
+        self.sample_keys
+            .entry(keys)
+            .or_default()
+            .push(copied.clone());
+        copied
+    }
+}
@@ -6,8 +6,10 @@ use crate::four_cc::FourCC;
 use crate::generator::Generator;
 use crate::instrument::Instrument;
 use crate::instrument_info::InstrumentInfo;
+use crate::partial_load;
 use crate::preset::Preset;
 use crate::preset_info::PresetInfo;
+use crate::preset_selector::PresetSelector;
 use crate::read_counter::ReadCounter;
 use crate::sample_header::SampleHeader;
 use crate::zone::Zone;
@@ -19,7 +21,77 @@ pub(crate) struct SoundFontParameters {
     pub(crate) instruments: Vec<Instrument>,
 }
 
+/// The lists in the 'pdta' chunk, before the regions are built from them.
+pub(crate) struct ParameterLists {
+    preset_infos: Vec<PresetInfo>,
+    preset_bag: Vec<ZoneInfo>,
+    preset_generators: Vec<Generator>,
+    instrument_infos: Vec<InstrumentInfo>,
+    instrument_bag: Vec<ZoneInfo>,
+    instrument_generators: Vec<Generator>,
+    sample_headers: Vec<SampleHeader>,
+}
+
 impl SoundFontParameters {
+    pub(crate) fn new<R: Read>(reader: &mut R) -> Result<Self, SoundFontError> {
+        let lists = ParameterLists::new(reader)?;
+        SoundFontParameters::build(lists)
+    }
+
+    pub(crate) fn build(lists: ParameterLists) -> Result<Self, SoundFontError> {
+        let instrument_zones = Zone::create(&lists.instrument_bag, &lists.instrument_generators)?;
+        let instruments = Instrument::create(
+            &lists.instrument_infos,
+            &instrument_zones,
+            &lists.sample_headers,
+        )?;
+
+        let preset_zones = Zone::create(&lists.preset_bag, &lists.preset_generators)?;
+        let presets = Preset::create(&lists.preset_infos, &preset_zones, instruments.len())?;
+
+        Ok(Self {
+            sample_headers: lists.sample_headers,
+            presets,
+            instruments,
+        })
+    }
+
+    /// Builds the selected presets and only the instruments they use.
+    pub(crate) fn build_selected(
+        lists: ParameterLists,
+        selectors: &[PresetSelector],
+    ) -> Result<Self, SoundFontError> {
+        if lists.instrument_infos.len() <= 1 {
+            return Err(SoundFontError::InstrumentNotFound);
+        }
+
+        // The last one is the terminator.
+        let instrument_count = lists.instrument_infos.len() - 1;
+
+        let preset_zones = Zone::create(&lists.preset_bag, &lists.preset_generators)?;
+        let presets = Preset::create(&lists.preset_infos, &preset_zones, instrument_count)?;
+        let (presets, instrument_ids) = partial_load::select_presets(presets, selectors)?;
+
+        let instrument_zones = Zone::create(&lists.instrument_bag, &lists.instrument_generators)?;
+        let mut instruments: Vec<Instrument> = Vec::new();
+        for instrument_id in instrument_ids {
+            instruments.push(Instrument::new(
+                &lists.instrument_infos[instrument_id],
+                instrument_id,
+                &instrument_zones,
+                &lists.sample_headers,
+            )?);
+        }
+
+        Ok(Self {
+            sample_headers: lists.sample_headers,
+            presets,
+            instruments,
+        })
+    }
+}
+
+impl ParameterLists {
     pub(crate) fn new<R: Read>(reader: &mut R) -> Result<Self, SoundFontError> {
         let chunk_id = BinaryReader::read_four_cc(reader)?;
         if chunk_id != b"LIST" {
@@ -91,17 +163,14 @@ impl SoundFontParameters {
             FourCC::from_bytes(*b"SHDR"),
         ))?;
 
-        let instrument_zones = Zone::create(&instrument_bag, &instrument_generators)?;
-        let instruments =
-            Instrument::create(&instrument_infos, &instrument_zones, &sample_headers)?;
-
-        let preset_zones = Zone::create(&preset_bag, &preset_generators)?;
-        let presets = Preset::create(&preset_infos, &preset_zones, &instruments)?;
-
         Ok(Self {
+            preset_infos,
+            preset_bag,
+            preset_generators,
+            instrument_infos,
+            instrument_bag,
+            instrument_generators,
             sample_headers,
-            presets,
-            instruments,
         })
     }
 }
@@ -1,5 +1,5 @@
-use std::io::Read;
-use std::slice;
+use std::io::{Read, Seek, SeekFrom};
+use std::ops::Range;
 
 use crate::binary_reader::BinaryReader;
 use crate::error::SoundFontError;
@@ -46,12 +46,61 @@ impl SoundFontSampleData {
             None => return Err(SoundFontError::SampleDataNotFound),
         };
 
-        let ptr = wave_data.as_ptr() as *const u8;
-        let four_cc = unsafe { slice::from_raw_parts(ptr, 4) };
-        if four_cc == b"OggS" {
+        let is_vorbis = match wave_data[..] {
+            [first, second, ..] => {
+                let (first, second) = (first.to_le_bytes(), second.to_le_bytes());
+                [first[0], first[1], second[0], second[1]] == *b"OggS"
+            }
+            _ => false,
+        };
+        if is_vorbis {
             return Err(SoundFontError::UnsupportedSampleFormat);
         }
 
         Ok(Self { wave_data })
     }
+
+    /// Finds the sample data without reading it.
+    /// Returns the range of the sample data in bytes from the start of the stream.
+    pub(crate) fn locate<R: Read + Seek>(reader: &mut R) -> Result<Range<usize>, SoundFontError> {
+        let chunk_id = BinaryReader::read_four_cc(reader)?;
+        if chunk_id != b"LIST" {
+            return Err(SoundFontError::ListChunkNotFound);
+        }
+
+        let size = BinaryReader::read_u32(reader)? as u64;
+        let end = reader.stream_position()? + size;
+
+        let list_type = BinaryReader::read_four_cc(reader)?;
+        if list_type != b"sdta" {
+            return Err(SoundFontError::InvalidListChunkType {
+                expected: FourCC::from_bytes(*b"sdta"),
+                actual: list_type,
+            });
+        }
+
+        let mut wave_data: Option<Range<usize>> = None;
+
+        while reader.stream_position()? < end {
+            let id = BinaryReader::read_four_cc(reader)?;
+            let size = BinaryReader::read_u32(reader)? as usize;
+            let position = reader.stream_position()? as usize;
+
+            match id.as_bytes() {
+                b"smpl" => {
+                    if size >= 4 && BinaryReader::read_four_cc(reader)? == b"OggS" {
+                        return Err(SoundFontError::UnsupportedSampleFormat);
+                    }
+                    // An odd byte at the end is not a part of any sample.
+                    wave_data = Some(position..position + size / 2 * 2);
+                }
+                b"sm24" => (),
+                _ => return Err(SoundFontError::ListContainsUnknownId(id)),
+            }
+
+            reader.seek(SeekFrom::Start((position + size) as u64))?;
+        }
+
+        wave_data.ok_or(SoundFontError::SampleDataNotFound)
+    }
 }
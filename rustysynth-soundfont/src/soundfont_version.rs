@@ -5,6 +5,7 @@ use crate::binary_reader::BinaryReader;
 
 /// Reperesents the version of a SoundFont.
 #[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct SoundFontVersion {
     pub(crate) major: i16,
     pub(crate) minor: i16,
@@ -0,0 +1,324 @@
+use std::io;
+use std::io::Write;
+
+use crate::generator_type::GeneratorType;
+use crate::instrument_region;
+use crate::preset_region;
+use crate::soundfont::SoundFont;
+use crate::soundfont_info::SoundFontInfo;
+
+type Generators = [i16; GeneratorType::COUNT];
+
+/// Writes the SoundFont in the SF2 format.
+/// Each region is written as a zone without the global zone, and the modulators are not written.
+pub(crate) fn write<W: Write>(sound_font: &SoundFont, writer: &mut W) -> Result<(), io::Error> {
+    let info = create_info_list(&sound_font.info);
+    let parameters = create_parameter_list(sound_font)?;
+
+    let sample_count = sound_font.wave_data.len();
+    let sample_data_size = 4 + 8 + 2 * sample_count;
+    let riff_size = 4 + info.len() + (8 + sample_data_size) + parameters.len();
+    let riff_size =
+        u32::try_from(riff_size).map_err(|_| invalid_input("the SoundFont is too large"))?;
+
+    writer.write_all(b"RIFF")?;
+    writer.write_all(&riff_size.to_le_bytes())?;
+    writer.write_all(b"sfbk")?;
+
+    writer.write_all(&info)?;
+
+    writer.write_all(b"LIST")?;
+    writer.write_all(&(sample_data_size as u32).to_le_bytes())?;
+    writer.write_all(b"sdta")?;
+    writer.write_all(b"smpl")?;
+    writer.write_all(&(2 * sample_count as u32).to_le_bytes())?;
+    const BLOCK_SIZE: usize = 4096;
+    let mut block: Vec<u8> = Vec::with_capacity(2 * BLOCK_SIZE);
+    for start in (0..sample_count).step_by(BLOCK_SIZE) {
+        let end = (start + BLOCK_SIZE).min(sample_count);
+        block.clear();
+        for value in sound_font.wave_data.get_samples(start..end).iter() {
+            block.extend_from_slice(&value.to_le_bytes());
+        }
+        writer.write_all(&block)?;
+    }
+
+    writer.write_all(&parameters)?;
+
+    Ok(())
+}
+
+fn create_info_list(info: &SoundFontInfo) -> Vec<u8> {
+    let mut list = ListWriter::new(b"INFO");
+
+    // The version 2.01 is the minimum which has every feature used here.
+    let (major, minor) = if info.version.major >= 2 {
+        (info.version.major, info.version.minor)
+    } else {
+        (2, 1)
+    };
+    let mut version: Vec<u8> = Vec::new();
+    version.extend_from_slice(&major.to_le_bytes());
+    version.extend_from_slice(&minor.to_le_bytes());
+    list.add_chunk(b"ifil", &version);
+
+    let target_sound_engine = match info.target_sound_engine.as_str() {
+        "" => "EMU8000",
+        value => value,
+    };
+    list.add_string(b"isng", target_sound_engine);
+
+    let bank_name = match info.bank_name.as_str() {
+        "" => "General MIDI",
+        value => value,
+    };
+    list.add_string(b"INAM", bank_name);
+
+    if !info.rom_name.is_empty() {
+        list.add_string(b"irom", &info.rom_name);
+        let mut rom_version: Vec<u8> = Vec::new();
+        rom_version.extend_from_slice(&info.rom_version.major.to_le_bytes());
+        rom_version.extend_from_slice(&info.rom_version.minor.to_le_bytes());
+        list.add_chunk(b"iver", &rom_version);
+    }
+
+    for (id, value) in [
+        (b"ICRD", &info.creation_date),
+        (b"IENG", &info.author),
+        (b"IPRD", &info.target_product),
+        (b"ICOP", &info.copyright),
+        (b"ICMT", &info.comments),
+        (b"ISFT", &info.tools),
+    ] {
+        if !value.is_empty() {
+            list.add_string(id, value);
+        }
+    }
+
+    list.finish()
+}
+
+fn create_parameter_list(sound_font: &SoundFont) -> Result<Vec<u8>, io::Error> {
+    let mut phdr: Vec<u8> = Vec::new();
+    let mut pbag: Vec<u8> = Vec::new();
+    let mut pgen: Vec<u8> = Vec::new();
+    let mut generator_count: usize = 0;
+    let mut zone_count: usize = 0;
+
+    for preset in &sound_font.presets {
+        write_name(&mut phdr, &preset.name);
+        phdr.extend_from_slice(&(preset.patch_number as u16).to_le_bytes());
+        phdr.extend_from_slice(&(preset.bank_number as u16).to_le_bytes());
+        phdr.extend_from_slice(&to_index(zone_count)?.to_le_bytes());
+        phdr.extend_from_slice(&preset.library.to_le_bytes());
+        phdr.extend_from_slice(&preset.genre.to_le_bytes());
+        phdr.extend_from_slice(&preset.morphology.to_le_bytes());
+
+        let zones: Vec<Vec<(u16, i16)>> = preset
+            .regions
+            .iter()
+            .map(|region| {
+                let mut gs = region.gs;
+                gs[GeneratorType::INSTRUMENT as usize] = region.instrument as i16;
+                get_generators(
+                    &gs,
+                    &preset_region::default_generators(),
+                    GeneratorType::INSTRUMENT,
+                )
+            })
+            .collect();
+        write_zones(
+            &mut pbag,
+            &mut pgen,
+            &mut zone_count,
+            &mut generator_count,
+            &zones,
+        )?;
+    }
+    write_name(&mut phdr, "EOP");
+    phdr.extend_from_slice(&[0; 4]);
+    phdr.extend_from_slice(&to_index(zone_count)?.to_le_bytes());
+    phdr.extend_from_slice(&[0; 12]);
+    write_bag(&mut pbag, generator_count)?;
+    pgen.extend_from_slice(&[0; 4]);
+
+    let mut inst: Vec<u8> = Vec::new();
+    let mut ibag: Vec<u8> = Vec::new();
+    let mut igen: Vec<u8> = Vec::new();
+    let mut generator_count: usize = 0;
+    let mut zone_count: usize = 0;
+
+    for instrument in &sound_font.instruments {
+        write_name(&mut inst, &instrument.name);
+        inst.extend_from_slice(&to_index(zone_count)?.to_le_bytes());
+
+        let zones: Vec<Vec<(u16, i16)>> = instrument
+            .regions
+            .iter()
+            .map(|region| {
+                get_generators(
+                    &region.gs,
+                    &instrument_region::default_generators(),
+                    GeneratorType::SAMPLE_ID,
+                )
+            })
+            .collect();
+        write_zones(
+            &mut ibag,
+            &mut igen,
+            &mut zone_count,
+            &mut generator_count,
+            &zones,
+        )?;
+    }
+    write_name(&mut inst, "EOI");
+    inst.extend_from_slice(&to_index(zone_count)?.to_le_bytes());
+    write_bag(&mut ibag, generator_count)?;
+    igen.extend_from_slice(&[0; 4]);
+
+    let mut shdr: Vec<u8> = Vec::new();
+    for header in &sound_font.sample_headers {
+        write_name(&mut shdr, &header.name);
+        shdr.extend_from_slice(&header.start.to_le_bytes());
+        shdr.extend_from_slice(&header.end.to_le_bytes());
+        shdr.extend_from_slice(&header.start_loop.to_le_bytes());
+        shdr.extend_from_slice(&header.end_loop.to_le_bytes());
+        shdr.extend_from_slice(&header.sample_rate.to_le_bytes());
+        shdr.push(header.original_pitch);
+        shdr.push(header.pitch_correction as u8);
+        shdr.extend_from_slice(&header.link.to_le_bytes());
+        shdr.extend_from_slice(&header.sample_type.to_le_bytes());
+    }
+    write_name(&mut shdr, "EOS");
+    shdr.extend_from_slice(&[0; 26]);
+
+    // The modulators are not kept, so the lists only have the terminators.
+    let modulators: [u8; 10] = [0; 10];
+
+    let mut list = ListWriter::new(b"pdta");
+    list.add_chunk(b"phdr", &phdr);
+    list.add_chunk(b"pbag", &pbag);
+    list.add_chunk(b"pmod", &modulators);
+    list.add_chunk(b"pgen", &pgen);
+    list.add_chunk(b"inst", &inst);
+    list.add_chunk(b"ibag", &ibag);
+    list.add_chunk(b"imod", &modulators);
+    list.add_chunk(b"igen", &igen);
+    list.add_chunk(b"shdr", &shdr);
+    Ok(list.finish())
+}
+
+// Gets the generators which differ from the default values, in the order required by the format.
+// The key range comes first, the velocity range second, and the instrument or the sample ID last.
+fn get_generators(gs: &Generators, defaults: &Generators, last: u16) -> Vec<(u16, i16)> {
+    let mut generators: Vec<(u16, i16)> = Vec::new();
+    for first in [GeneratorType::KEY_RANGE, GeneratorType::VELOCITY_RANGE] {
+        if gs[first as usize] != defaults[first as usize] {
+            generators.push((first, gs[first as usize]));
+        }
+    }
+    for (i, value) in gs.iter().enumerate() {
+        let generator_type = i as u16;
+        if generator_type == GeneratorType::KEY_RANGE
+            || generator_type == GeneratorType::VELOCITY_RANGE
+            || generator_type == last
+        {
+            continue;
+        }
+        if *value != defaults[i] {
+            generators.push((generator_type, *value));
+        }
+    }
+    generators.push((last, gs[last as usize]));
+    generators
+}
+
+fn write_zones(
+    bag: &mut Vec<u8>,
+    generators: &mut Vec<u8>,
+    zone_count: &mut usize,
+    generator_count: &mut usize,
+    zones: &[Vec<(u16, i16)>],
+) -> Result<(), io::Error> {
+    // A preset or an instrument needs at least one zone.
+    // A zone without generators is read as an empty global zone.
+    let empty: [Vec<(u16, i16)>; 1] = [Vec::new()];
+    let zones = if zones.is_empty() { &empty[..] } else { zones };
+
+    for zone in zones {
+        write_bag(bag, *generator_count)?;
+        for (generator_type, value) in zone {
+            generators.extend_from_slice(&generator_type.to_le_bytes());
+            generators.extend_from_slice(&value.to_le_bytes());
+        }
+        *generator_count += zone.len();
+        *zone_count += 1;
+    }
+    Ok(())
+}
+
+fn write_bag(bag: &mut Vec<u8>, generator_index: usize) -> Result<(), io::Error> {
+    bag.extend_from_slice(&to_index(generator_index)?.to_le_bytes());
+    // No modulators.
+    bag.extend_from_slice(&[0; 2]);
+    Ok(())
+}
+
+fn write_name(data: &mut Vec<u8>, name: &str) {
+    // The name is terminated by zero within 20 bytes.
+    let mut bytes: [u8; 20] = [0; 20];
+    for (i, c) in name.chars().filter(|c| c.is_ascii()).take(19).enumerate() {
+        bytes[i] = c as u8;
+    }
+    data.extend_from_slice(&bytes);
+}
+
+fn to_index(value: usize) -> Result<u16, io::Error> {
+    u16::try_from(value)
+        .map_err(|_| invalid_input("too many zones or generators for the SF2 format"))
+}
+
+fn invalid_input(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidInput, message)
+}
+
+struct ListWriter {
+    data: Vec<u8>,
+}
+
+impl ListWriter {
+    fn new(list_type: &[u8; 4]) -> Self {
+        let mut data: Vec<u8> = Vec::new();
+        data.extend_from_slice(b"LIST");
+        data.extend_from_slice(&[0; 4]);
+        data.extend_from_slice(list_type);
+        Self { data }
+    }
+
+    fn add_chunk(&mut self, id: &[u8; 4], content: &[u8]) {
+        self.data.extend_from_slice(id);
+        self.data
+            .extend_from_slice(&(content.len() as u32).to_le_bytes());
+        self.data.extend_from_slice(content);
+        // Chunks are padded to an even size.
+        if content.len() % 2 == 1 {
+            self.data.push(0);
+        }
+    }
+
+    fn add_string(&mut self, id: &[u8; 4], value: &str) {
+        // The string is terminated by zero and padded to an even size.
+        let mut content: Vec<u8> = value.bytes().filter(|c| c.is_ascii()).collect();
+        content.push(0);
+        if content.len() % 2 == 1 {
+            content.push(0);
+        }
+        self.add_chunk(id, &content);
+    }
+
+    fn finish(mut self) -> Vec<u8> {
+        let size = (self.data.len() - 8) as u32;
+        self.data[4..8].copy_from_slice(&size.to_le_bytes());
+        self.data
+    }
+}
@@ -13,16 +13,21 @@ impl Zone {
         }
     }
 
-    fn new(info: &ZoneInfo, generators: &[Generator]) -> Self {
-        let mut segment: Vec<Generator> = Vec::new();
-
-        for i in 0..info.generator_count {
-            segment.push(generators[(info.generator_index + i) as usize]);
+    fn new(info: &ZoneInfo, generators: &[Generator]) -> Result<Self, SoundFontError> {
+        if info.generator_count < 0 {
+            return Err(SoundFontError::InvalidZoneList);
         }
 
-        Self {
+        let start = info.generator_index as usize;
+        let end = start + info.generator_count as usize;
+        let segment = match generators.get(start..end) {
+            Some(value) => value.to_vec(),
+            None => return Err(SoundFontError::InvalidZoneList),
+        };
+
+        Ok(Self {
             generators: segment,
-        }
+        })
     }
 
     pub(crate) fn create(
@@ -38,7 +43,7 @@ impl Zone {
 
         let mut zones: Vec<Zone> = Vec::new();
         for info in infos.iter().take(count) {
-            zones.push(Zone::new(info, generators));
+            zones.push(Zone::new(info, generators)?);
         }
 
         Ok(zones)
@@ -0,0 +1,59 @@
+use std::error;
+use std::fmt;
+use std::io;
+
+/// Represents an error when loading a WAV file.
+#[derive(Debug)]
+pub enum WavError {
+    IoError(io::Error),
+    RiffChunkNotFound,
+    InvalidRiffChunkType([u8; 4]),
+    FormatChunkNotFound,
+    InvalidFormatChunk,
+    UnsupportedFormat {
+        format_tag: u16,
+        bits_per_sample: u16,
+    },
+    DataChunkNotFound,
+    InvalidSamplerChunk,
+}
+
+impl error::Error for WavError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            WavError::IoError(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for WavError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            WavError::IoError(err) => err.fmt(f),
+            WavError::RiffChunkNotFound => write!(f, "the RIFF chunk was not found"),
+            WavError::InvalidRiffChunkType(actual) => write!(
+                f,
+                "the type of the RIFF chunk must be 'WAVE', but was '{}'",
+                String::from_utf8_lossy(actual)
+            ),
+            WavError::FormatChunkNotFound => write!(f, "the 'fmt ' chunk was not found"),
+            WavError::InvalidFormatChunk => write!(f, "the 'fmt ' chunk is invalid"),
+            WavError::UnsupportedFormat {
+                format_tag,
+                bits_per_sample,
+            } => write!(
+                f,
+                "the sample format (tag {format_tag}, {bits_per_sample} bits) is not supported"
+            ),
+            WavError::DataChunkNotFound => write!(f, "the 'data' chunk was not found"),
+            WavError::InvalidSamplerChunk => write!(f, "the 'smpl' chunk is invalid"),
+        }
+    }
+}
+
+impl From<io::Error> for WavError {
+    fn from(err: io::Error) -> Self {
+        WavError::IoError(err)
+    }
+}
@@ -0,0 +1,65 @@
+mod error;
+
+mod wav_file;
+mod wav_sample;
+mod wav_sound;
+mod wav_writer;
+
+pub use self::error::WavError;
+pub use self::wav_file::{SampleLoop, SamplerInfo, WavFile};
+pub use self::wav_sample::{Envelope, WavSample};
+pub use self::wav_sound::WavSound;
+pub use self::wav_writer::WavWriter;
+use anyhow::{Result, anyhow};
+use rustysynth::{PresetId, SoundSource};
+
+/// An instrument built from WAV samples.
+/// Every preset ID plays the same instrument.
+#[derive(Debug, Default)]
+pub struct WavInstrument {
+    samples: Vec<WavSample>,
+}
+
+impl WavInstrument {
+    pub fn new(samples: Vec<WavSample>) -> Self {
+        Self { samples }
+    }
+
+    /// Adds a sample to the instrument.
+    /// If the key and velocity ranges of samples overlap, the one added first is used.
+    pub fn add_sample(&mut self, sample: WavSample) {
+        self.samples.push(sample);
+    }
+
+    /// Gets the samples of the instrument.
+    pub fn get_samples(&self) -> &[WavSample] {
+        &self.samples[..]
+    }
+}
+
+impl From<Vec<WavSample>> for WavInstrument {
+    fn from(samples: Vec<WavSample>) -> Self {
+        Self::new(samples)
+    }
+}
+
+impl From<WavSample> for WavInstrument {
+    fn from(sample: WavSample) -> Self {
+        Self::new(vec![sample])
+    }
+}
+
+impl SoundSource for WavInstrument {
+    #[allow(refining_impl_trait)]
+    fn get_sound(&mut self, _preset_id: PresetId, key: i32, velocity: i32) -> Result<WavSound<'_>> {
+        self.samples
+            .iter()
+            .find(|sample| sample.contains(key, velocity))
+            .map(|sample| WavSound { sample })
+            .ok_or_else(|| anyhow!("No samples found for key: {}, velocity: {}", key, velocity))
+    }
+
+    fn contains_preset(&self, _preset_id: PresetId) -> bool {
+        true
+    }
+}
@@ -0,0 +1,364 @@
+use std::io;
+use std::io::Read;
+
+use crate::error::WavError;
+
+const WAVE_FORMAT_PCM: u16 = 0x0001;
+const WAVE_FORMAT_IEEE_FLOAT: u16 = 0x0003;
+const WAVE_FORMAT_EXTENSIBLE: u16 = 0xFFFE;
+
+/// Represents a loop defined in the 'smpl' chunk of a WAV file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SampleLoop {
+    pub(crate) loop_type: u32,
+    pub(crate) start: u32,
+    pub(crate) end: u32,
+    pub(crate) play_count: u32,
+}
+
+impl SampleLoop {
+    /// Creates a loop.
+    ///
+    /// # Arguments
+    ///
+    /// * `loop_type` - The loop type (0 = forward, 1 = ping-pong, 2 = backward).
+    /// * `start` - The first sample of the loop.
+    /// * `end` - The last sample of the loop (inclusive).
+    /// * `play_count` - The number of times the loop is played (0 means infinite).
+    pub fn new(loop_type: u32, start: u32, end: u32, play_count: u32) -> Self {
+        Self {
+            loop_type,
+            start,
+            end,
+            play_count,
+        }
+    }
+
+    /// Gets the loop type (0 = forward, 1 = ping-pong, 2 = backward).
+    pub fn get_loop_type(&self) -> u32 {
+        self.loop_type
+    }
+
+    /// Gets the first sample of the loop.
+    pub fn get_start(&self) -> u32 {
+        self.start
+    }
+
+    /// Gets the last sample of the loop (inclusive).
+    pub fn get_end(&self) -> u32 {
+        self.end
+    }
+
+    /// Gets the number of times the loop is played (0 means infinite).
+    pub fn get_play_count(&self) -> u32 {
+        self.play_count
+    }
+}
+
+/// The sampler information stored in the 'smpl' chunk of a WAV file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SamplerInfo {
+    pub(crate) unity_note: u32,
+    pub(crate) pitch_fraction: u32,
+    pub(crate) loops: Vec<SampleLoop>,
+}
+
+impl SamplerInfo {
+    /// Creates the sampler information.
+    ///
+    /// # Arguments
+    ///
+    /// * `unity_note` - The MIDI key number at which the sample plays back at its original pitch.
+    /// * `pitch_fraction` - The fraction of a semitone up from the unity note, in units of 1/2^32.
+    /// * `loops` - The loops of the sample.
+    pub fn new(unity_note: u32, pitch_fraction: u32, loops: Vec<SampleLoop>) -> Self {
+        Self {
+            unity_note,
+            pitch_fraction,
+            loops,
+        }
+    }
+
+    fn read<R: Read>(reader: &mut R, size: usize) -> Result<Self, WavError> {
+        if size < 36 {
+            return Err(WavError::InvalidSamplerChunk);
+        }
+
+        let _manufacturer = read_u32(reader)?;
+        let _product = read_u32(reader)?;
+        let _sample_period = read_u32(reader)?;
+        let unity_note = read_u32(reader)?;
+        let pitch_fraction = read_u32(reader)?;
+        let _smpte_format = read_u32(reader)?;
+        let _smpte_offset = read_u32(reader)?;
+        let loop_count = read_u32(reader)? as usize;
+        let _sampler_data = read_u32(reader)?;
+
+        if loop_count > (size - 36) / 24 {
+            return Err(WavError::InvalidSamplerChunk);
+        }
+
+        let mut loops: Vec<SampleLoop> = Vec::new();
+        for _i in 0..loop_count {
+            let _cue_point_id = read_u32(reader)?;
+            let loop_type = read_u32(reader)?;
+            let start = read_u32(reader)?;
+            let end = read_u32(reader)?;
+            let _fraction = read_u32(reader)?;
+            let play_count = read_u32(reader)?;
+            loops.push(SampleLoop {
+                loop_type,
+                start,
+                end,
+                play_count,
+            });
+        }
+
+        discard_data(reader, size - 36 - 24 * loop_count)?;
+
+        Ok(Self {
+            unity_note,
+            pitch_fraction,
+            loops,
+        })
+    }
+
+    /// Gets the MIDI key number at which the sample plays back at its original pitch.
+    pub fn get_unity_note(&self) -> u32 {
+        self.unity_note
+    }
+
+    /// Gets the fraction of a semitone up from the unity note, in units of 1/2^32.
+    pub fn get_pitch_fraction(&self) -> u32 {
+        self.pitch_fraction
+    }
+
+    /// Gets the pitch fraction in cents.
+    pub fn get_pitch_fraction_cents(&self) -> f32 {
+        (100.0 * self.pitch_fraction as f64 / 4294967296.0) as f32
+    }
+
+    /// Gets the loops of the sample.
+    pub fn get_loops(&self) -> &[SampleLoop] {
+        &self.loops[..]
+    }
+}
+
+/// Represents a WAV file.
+/// The sample data is converted to 16-bit mono regardless of the original format.
+#[derive(Debug)]
+pub struct WavFile {
+    pub(crate) sample_rate: i32,
+    pub(crate) channels: u16,
+    pub(crate) bits_per_sample: u16,
+    pub(crate) wave_data: Vec<i16>,
+    pub(crate) sampler_info: Option<SamplerInfo>,
+}
+
+struct Format {
+    format_tag: u16,
+    channels: u16,
+    sample_rate: u32,
+    block_align: u16,
+    bits_per_sample: u16,
+}
+
+impl WavFile {
+    /// Loads a WAV file from the stream.
+    ///
+    /// # Arguments
+    ///
+    /// * `reader` - The data stream used to load the WAV file.
+    pub fn new<R: Read>(reader: &mut R) -> Result<Self, WavError> {
+        if &read_four_cc(reader)? != b"RIFF" {
+            return Err(WavError::RiffChunkNotFound);
+        }
+
+        let _size = read_u32(reader)?;
+
+        let form_type = read_four_cc(reader)?;
+        if &form_type != b"WAVE" {
+            return Err(WavError::InvalidRiffChunkType(form_type));
+        }
+
+        let mut format: Option<Format> = None;
+        let mut data: Option<Vec<u8>> = None;
+        let mut sampler_info: Option<SamplerInfo> = None;
+
+        loop {
+            let id = match read_four_cc(reader) {
+                Ok(value) => value,
+                Err(err) if err.kind() == io::ErrorKind::UnexpectedEof => break,
+                Err(err) => return Err(err.into()),
+            };
+            let size = read_u32(reader)? as usize;
+
+            match &id {
+                b"fmt " => format = Some(Format::new(reader, size)?),
+                b"data" => {
+                    let mut buffer = Vec::new();
+                    reader.take(size as u64).read_to_end(&mut buffer)?;
+                    if buffer.len() != size {
+                        return Err(io::Error::from(io::ErrorKind::UnexpectedEof).into());
+                    }
+                    data = Some(buffer);
+                }
+                b"smpl" => sampler_info = Some(SamplerInfo::read(reader, size)?),
+                _ => discard_data(reader, size)?,
+            }
+
+            // Chunks are padded to an even size.
+            if size % 2 == 1 && discard_data(reader, 1).is_err() {
+                break;
+            }
+        }
+
+        let format = format.ok_or(WavError::FormatChunkNotFound)?;
+        let data = data.ok_or(WavError::DataChunkNotFound)?;
+        let wave_data = format.decode(&data)?;
+
+        Ok(Self {
+            sample_rate: format.sample_rate as i32,
+            channels: format.channels,
+            bits_per_sample: format.bits_per_sample,
+            wave_data,
+            sampler_info,
+        })
+    }
+
+    /// Gets the sample rate of the WAV file.
+    pub fn get_sample_rate(&self) -> i32 {
+        self.sample_rate
+    }
+
+    /// Gets the number of channels in the original file.
+    pub fn get_channels(&self) -> u16 {
+        self.channels
+    }
+
+    /// Gets the bits per sample in the original file.
+    pub fn get_bits_per_sample(&self) -> u16 {
+        self.bits_per_sample
+    }
+
+    /// Gets the sample data, mixed down to 16-bit mono.
+    pub fn get_wave_data(&self) -> &[i16] {
+        &self.wave_data[..]
+    }
+
+    /// Gets the information in the 'smpl' chunk, if present.
+    pub fn get_sampler_info(&self) -> Option<&SamplerInfo> {
+        self.sampler_info.as_ref()
+    }
+}
+
+impl Format {
+    fn new<R: Read>(reader: &mut R, size: usize) -> Result<Self, WavError> {
+        if size < 16 {
+            return Err(WavError::InvalidFormatChunk);
+        }
+
+        let mut format_tag = read_u16(reader)?;
+        let channels = read_u16(reader)?;
+        let sample_rate = read_u32(reader)?;
+        let _byte_rate = read_u32(reader)?;
+        let block_align = read_u16(reader)?;
+        let bits_per_sample = read_u16(reader)?;
+
+        let mut remaining = size - 16;
+        if format_tag == WAVE_FORMAT_EXTENSIBLE && remaining >= 10 {
+            // cbSize, wValidBitsPerSample, dwChannelMask,
+            // followed by the sub-format GUID whose first two bytes are the format tag.
+            let _extension_size = read_u16(reader)?;
+            let _valid_bits = read_u16(reader)?;
+            let _channel_mask = read_u32(reader)?;
+            format_tag = read_u16(reader)?;
+            remaining -= 10;
+        }
+        discard_data(reader, remaining)?;
+
+        if channels == 0 || sample_rate == 0 || block_align == 0 {
+            return Err(WavError::InvalidFormatChunk);
+        }
+
+        Ok(Self {
+            format_tag,
+            channels,
+            sample_rate,
+            block_align,
+            bits_per_sample,
+        })
+    }
+
+    fn decode(&self, data: &[u8]) -> Result<Vec<i16>, WavError> {
+        let decode_sample: fn(&[u8]) -> f32 = match (self.format_tag, self.bits_per_sample) {
+            (WAVE_FORMAT_PCM, 8) => |b| (b[0] as f32 - 128.0) / 128.0,
+            (WAVE_FORMAT_PCM, 16) => |b| i16::from_le_bytes([b[0], b[1]]) as f32 / 32768.0,
+            (WAVE_FORMAT_PCM, 24) => {
+                |b| (i32::from_le_bytes([0, b[0], b[1], b[2]]) >> 8) as f32 / 8388608.0
+            }
+            (WAVE_FORMAT_PCM, 32) => {
+                |b| i32::from_le_bytes([b[0], b[1], b[2], b[3]]) as f32 / 2147483648.0
+            }
+            (WAVE_FORMAT_IEEE_FLOAT, 32) => |b| f32::from_le_bytes([b[0], b[1], b[2], b[3]]),
+            (WAVE_FORMAT_IEEE_FLOAT, 64) => {
+                |b| f64::from_le_bytes([b[0], b[1], b[2], b[3], b[4], b[5], b[6], b[7]]) as f32
+            }
+            (format_tag, bits_per_sample) => {
+                return Err(WavError::UnsupportedFormat {
+                    format_tag,
+                    bits_per_sample,
+                });
+            }
+        };
+
+        let bytes_per_sample = self.bits_per_sample as usize / 8;
+        let channels = self.channels as usize;
+        if (self.block_align as usize) < bytes_per_sample * channels {
+            return Err(WavError::InvalidFormatChunk);
+        }
+
+        // Multi-channel files are mixed down to mono.
+        let scale = 1.0 / channels as f32;
+        let wave_data = data
+            .chunks_exact(self.block_align as usize)
+            .map(|frame| {
+                let sum: f32 = frame
+                    .chunks_exact(bytes_per_sample)
+                    .take(channels)
+                    .map(decode_sample)
+                    .sum();
+                (32768.0 * scale * sum).clamp(-32768.0, 32767.0) as i16
+            })
+            .collect();
+
+        Ok(wave_data)
+    }
+}
+
+fn read_u16<R: Read>(reader: &mut R) -> Result<u16, io::Error> {
+    let mut data: [u8; 2] = [0; 2];
+    reader.read_exact(&mut data)?;
+    Ok(u16::from_le_bytes(data))
+}
+
+fn read_u32<R: Read>(reader: &mut R) -> Result<u32, io::Error> {
+    let mut data: [u8; 4] = [0; 4];
+    reader.read_exact(&mut data)?;
+    Ok(u32::from_le_bytes(data))
+}
+
+fn read_four_cc<R: Read>(reader: &mut R) -> Result<[u8; 4], io::Error> {
+    let mut data: [u8; 4] = [0; 4];
+    reader.read_exact(&mut data)?;
+    Ok(data)
+}
+
+fn discard_data<R: Read>(reader: &mut R, size: usize) -> Result<(), io::Error> {
+    let copied = io::copy(&mut reader.take(size as u64), &mut io::sink())?;
+    if copied == size as u64 {
+        Ok(())
+    } else {
+        Err(io::Error::from(io::ErrorKind::UnexpectedEof))
+    }
+}
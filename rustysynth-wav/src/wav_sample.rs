@@ -0,0 +1,232 @@
+use std::io::Read;
+use std::sync::Arc;
+
+use rustysynth::soundfont_math::cents_to_hertz;
+use rustysynth::{FilterType, LoopMode};
+
+use crate::error::WavError;
+use crate::wav_file::WavFile;
+
+/// The volume envelope applied to a WAV sample.
+/// All times are in seconds.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Envelope {
+    pub delay: f32,
+    pub attack: f32,
+    pub hold: f32,
+    pub decay: f32,
+    /// The sustain level as an attenuation in decibels.
+    pub sustain: f32,
+    pub release: f32,
+}
+
+impl Default for Envelope {
+    fn default() -> Self {
+        Self {
+            delay: 0.001,
+            attack: 0.001,
+            hold: 0.001,
+            decay: 0.001,
+            sustain: 0.0,
+            release: 0.05,
+        }
+    }
+}
+
+/// Represents a WAV sample mapped to a key and velocity range.
+#[derive(Debug, Clone)]
+pub struct WavSample {
+    pub(crate) wave_data: Arc<[i16]>,
+    pub(crate) sample_rate: i32,
+    pub(crate) root_key: i32,
+    pub(crate) fine_tune: i32,
+    pub(crate) key_range_start: i32,
+    pub(crate) key_range_end: i32,
+    pub(crate) velocity_range_start: i32,
+    pub(crate) velocity_range_end: i32,
+    pub(crate) loop_mode: LoopMode,
+    pub(crate) start_loop: i32,
+    pub(crate) end_loop: i32,
+    pub(crate) attenuation: f32,
+    pub(crate) envelope: Envelope,
+    pub(crate) filter_type: FilterType,
+    pub(crate) filter_cutoff: f32,
+}
+
+impl WavSample {
+    /// Loads a WAV sample from the stream.
+    /// The root key and the loop points are taken from the 'smpl' chunk if present.
+    ///
+    /// # Arguments
+    ///
+    /// * `reader` - The data stream used to load the WAV file.
+    pub fn new<R: Read>(reader: &mut R) -> Result<Self, WavError> {
+        Ok(WavSample::from(WavFile::new(reader)?))
+    }
+
+    /// Sets the root key, at which the sample plays back at its original pitch.
+    pub fn set_root_key(&mut self, root_key: i32) {
+        self.root_key = root_key;
+    }
+
+    /// Sets the fine tuning in cents.
+    pub fn set_fine_tune(&mut self, fine_tune: i32) {
+        self.fine_tune = fine_tune;
+    }
+
+    /// Sets the range of keys covered by the sample (inclusive).
+    pub fn set_key_range(&mut self, start: i32, end: i32) {
+        self.key_range_start = start;
+        self.key_range_end = end;
+    }
+
+    /// Sets the range of velocities covered by the sample (inclusive).
+    pub fn set_velocity_range(&mut self, start: i32, end: i32) {
+        self.velocity_range_start = start;
+        self.velocity_range_end = end;
+    }
+
+    /// Sets the loop points and the loop mode.
+    ///
+    /// # Arguments
+    ///
+    /// * `start` - The first sample of the loop.
+    /// * `end` - The first sample after the loop.
+    /// * `loop_mode` - How the sample loops during playback.
+    pub fn set_loop(&mut self, start: i32, end: i32, loop_mode: LoopMode) {
+        let length = self.wave_data.len() as i32;
+        self.start_loop = start.clamp(0, length);
+        self.end_loop = end.clamp(self.start_loop, length);
+        self.loop_mode = if self.end_loop > self.start_loop {
+            loop_mode
+        } else {
+            LoopMode::NoLoop
+        };
+    }
+
+    /// Sets the attenuation in decibels.
+    pub fn set_attenuation(&mut self, attenuation: f32) {
+        self.attenuation = attenuation;
+    }
+
+    /// Sets the volume envelope.
+    pub fn set_envelope(&mut self, envelope: Envelope) {
+        self.envelope = envelope;
+    }
+
+    /// Sets the filter applied to the sample.
+    ///
+    /// # Arguments
+    ///
+    /// * `filter_type` - The type of the filter.
+    /// * `cutoff` - The cutoff frequency in Hz.
+    pub fn set_filter(&mut self, filter_type: FilterType, cutoff: f32) {
+        self.filter_type = filter_type;
+        self.filter_cutoff = cutoff;
+    }
+
+    /// Checks if the sample covers the given key and velocity.
+    /// Returns `true` if the sample covers the given key and velocity.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key of a note.
+    /// * `velocity` - The velocity of a note.
+    pub fn contains(&self, key: i32, velocity: i32) -> bool {
+        let contains_key = self.key_range_start <= key && key <= self.key_range_end;
+        let contains_velocity =
+            self.velocity_range_start <= velocity && velocity <= self.velocity_range_end;
+        contains_key && contains_velocity
+    }
+
+    /// Gets the sample data.
+    pub fn get_wave_data(&self) -> &[i16] {
+        &self.wave_data[..]
+    }
+
+    /// Gets the sample rate of the sample.
+    pub fn get_sample_rate(&self) -> i32 {
+        self.sample_rate
+    }
+
+    /// Gets the root key of the sample.
+    pub fn get_root_key(&self) -> i32 {
+        self.root_key
+    }
+
+    /// Gets the fine tuning in cents.
+    pub fn get_fine_tune(&self) -> i32 {
+        self.fine_tune
+    }
+
+    /// Gets the loop mode of the sample.
+    pub fn get_loop_mode(&self) -> LoopMode {
+        self.loop_mode
+    }
+
+    /// Gets the first sample of the loop.
+    pub fn get_start_loop(&self) -> i32 {
+        self.start_loop
+    }
+
+    /// Gets the first sample after the loop.
+    pub fn get_end_loop(&self) -> i32 {
+        self.end_loop
+    }
+
+    /// Gets the volume envelope.
+    pub fn get_envelope(&self) -> &Envelope {
+        &self.envelope
+    }
+
+    /// Gets the type of the filter.
+    pub fn get_filter_type(&self) -> FilterType {
+        self.filter_type
+    }
+
+    /// Gets the cutoff frequency of the filter in Hz.
+    pub fn get_filter_cutoff(&self) -> f32 {
+        self.filter_cutoff
+    }
+}
+
+impl From<WavFile> for WavSample {
+    fn from(file: WavFile) -> Self {
+        let mut sample = Self {
+            wave_data: Arc::from(file.wave_data.into_boxed_slice()),
+            sample_rate: file.sample_rate,
+            root_key: 60,
+            fine_tune: 0,
+            key_range_start: 0,
+            key_range_end: 127,
+            velocity_range_start: 0,
+            velocity_range_end: 127,
+            loop_mode: LoopMode::NoLoop,
+            start_loop: 0,
+            end_loop: 0,
+            attenuation: 0.0,
+            envelope: Envelope::default(),
+            filter_type: FilterType::LowPass,
+            // The SoundFont default (13500 cents) which leaves the filter open.
+            filter_cutoff: cents_to_hertz(13500.0),
+        };
+
+        if let Some(info) = file.sampler_info {
+            // The pitch fraction raises the recorded pitch, so it is compensated by the fine tuning.
+            sample.root_key = info.unity_note.min(127) as i32;
+            sample.fine_tune = -info.get_pitch_fraction_cents().round() as i32;
+
+            // Only the first forward loop is used.
+            if let Some(value) = info.loops.iter().find(|value| value.loop_type == 0) {
+                // The end point in the 'smpl' chunk is inclusive.
+                sample.set_loop(
+                    value.start as i32,
+                    (value.end as i32).saturating_add(1),
+                    LoopMode::Continuous,
+                );
+            }
+        }
+
+        sample
+    }
+}
@@ -0,0 +1,120 @@
+use rustysynth::{FilterType, LoopMode, Sound, View};
+
+use crate::wav_sample::WavSample;
+
+// The SoundFont default for the unused envelope and LFO times (-12000 timecents).
+const MINIMUM_TIME: f32 = 0.001;
+
+pub struct WavSound<'a> {
+    pub(crate) sample: &'a WavSample,
+}
+
+impl Sound for WavSound<'_> {
+    fn get_wave_data(&self) -> View<i16> {
+        View {
+            data: self.sample.wave_data.clone(),
+            start: 0,
+            end: self.sample.wave_data.len(),
+        }
+    }
+
+    fn sample_sample_rate(&self) -> i32 {
+        self.sample.sample_rate
+    }
+
+    fn get_sample_start_loop(&self) -> i32 {
+        self.sample.start_loop
+    }
+
+    fn get_sample_end_loop(&self) -> i32 {
+        self.sample.end_loop
+    }
+
+    fn get_initial_filter_cutoff_frequency(&self) -> f32 {
+        self.sample.filter_cutoff
+    }
+
+    fn get_filter_type(&self) -> FilterType {
+        self.sample.filter_type
+    }
+
+    fn get_reverb_effects_send(&self) -> f32 {
+        0.0
+    }
+
+    fn get_delay_modulation_lfo(&self) -> f32 {
+        MINIMUM_TIME
+    }
+
+    fn get_frequency_modulation_lfo(&self) -> f32 {
+        0.0
+    }
+
+    fn get_delay_vibrato_lfo(&self) -> f32 {
+        MINIMUM_TIME
+    }
+
+    fn get_frequency_vibrato_lfo(&self) -> f32 {
+        0.0
+    }
+
+    fn get_delay_modulation_envelope(&self) -> f32 {
+        MINIMUM_TIME
+    }
+
+    fn get_attack_modulation_envelope(&self) -> f32 {
+        MINIMUM_TIME
+    }
+
+    fn get_hold_modulation_envelope(&self) -> f32 {
+        MINIMUM_TIME
+    }
+
+    fn get_decay_modulation_envelope(&self) -> f32 {
+        MINIMUM_TIME
+    }
+
+    fn get_release_modulation_envelope(&self) -> f32 {
+        MINIMUM_TIME
+    }
+
+    fn get_delay_volume_envelope(&self) -> f32 {
+        self.sample.envelope.delay
+    }
+
+    fn get_attack_volume_envelope(&self) -> f32 {
+        self.sample.envelope.attack
+    }
+
+    fn get_hold_volume_envelope(&self) -> f32 {
+        self.sample.envelope.hold
+    }
+
+    fn get_decay_volume_envelope(&self) -> f32 {
+        self.sample.envelope.decay
+    }
+
+    fn get_sustain_volume_envelope(&self) -> f32 {
+        self.sample.envelope.sustain
+    }
+
+    fn get_release_volume_envelope(&self) -> f32 {
+        self.sample.envelope.release
+    }
+
+    fn get_initial_attenuation(&self) -> f32 {
+        self.sample.attenuation
+    }
+
+    fn get_fine_tune(&self) -> i32 {
+        self.sample.fine_tune
+    }
+
+    fn get_sample_modes(&self) -> LoopMode {
+        self.sample.loop_mode
+    }
+
+    fn get_root_key(&self) -> i32 {
+        self.sample.root_key
+    }
+}
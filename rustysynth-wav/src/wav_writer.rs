@@ -0,0 +1,130 @@
+use std::io;
+use std::io::Write;
+
+use crate::wav_file::SamplerInfo;
+
+const WAVE_FORMAT_PCM: u16 = 0x0001;
+
+/// Writes 16-bit PCM WAV files.
+#[derive(Debug, Clone)]
+pub struct WavWriter<'a> {
+    sample_rate: u32,
+    channels: Vec<&'a [i16]>,
+    sampler_info: Option<SamplerInfo>,
+}
+
+impl<'a> WavWriter<'a> {
+    /// Creates a writer without any channels.
+    ///
+    /// # Arguments
+    ///
+    /// * `sample_rate` - The sample rate of the WAV file.
+    pub fn new(sample_rate: u32) -> Self {
+        Self {
+            sample_rate,
+            channels: Vec::new(),
+            sampler_info: None,
+        }
+    }
+
+    /// Adds a channel to the WAV file.
+    /// Channels shorter than the others are padded with silence.
+    pub fn add_channel(&mut self, data: &'a [i16]) {
+        self.channels.push(data);
+    }
+
+    /// Sets the information written to the 'smpl' chunk.
+    pub fn set_sampler_info(&mut self, sampler_info: SamplerInfo) {
+        self.sampler_info = Some(sampler_info);
+    }
+
+    /// Writes the WAV file to the stream.
+    ///
+    /// # Arguments
+    ///
+    /// * `writer` - The data stream used to write the WAV file.
+    pub fn write<W: Write>(&self, writer: &mut W) -> Result<(), io::Error> {
+        let channels = self.channels.len();
+        if channels == 0 || channels > u16::MAX as usize {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "the number of channels must be between 1 and 65535",
+            ));
+        }
+
+        let length = self.channels.iter().map(|data| data.len()).max().unwrap();
+        let block_align = 2 * channels;
+        let data_size = block_align * length;
+        let sampler_size = self
+            .sampler_info
+            .as_ref()
+            .map_or(0, |info| 8 + 36 + 24 * info.loops.len());
+        let riff_size = 4 + (8 + 16) + (8 + data_size + data_size % 2) + sampler_size;
+        if riff_size > u32::MAX as usize {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "the WAV file is too large",
+            ));
+        }
+
+        writer.write_all(b"RIFF")?;
+        write_u32(writer, riff_size as u32)?;
+        writer.write_all(b"WAVE")?;
+
+        writer.write_all(b"fmt ")?;
+        write_u32(writer, 16)?;
+        write_u16(writer, WAVE_FORMAT_PCM)?;
+        write_u16(writer, channels as u16)?;
+        write_u32(writer, self.sample_rate)?;
+        write_u32(writer, self.sample_rate.saturating_mul(block_align as u32))?;
+        write_u16(writer, block_align as u16)?;
+        write_u16(writer, 16)?;
+
+        writer.write_all(b"data")?;
+        write_u32(writer, data_size as u32)?;
+        let mut frame: Vec<u8> = vec![0; block_align];
+        for t in 0..length {
+            for (ch, data) in self.channels.iter().enumerate() {
+                let value = data.get(t).copied().unwrap_or(0);
+                frame[2 * ch..2 * ch + 2].copy_from_slice(&value.to_le_bytes());
+            }
+            writer.write_all(&frame)?;
+        }
+        // Chunks are padded to an even size.
+        if data_size % 2 == 1 {
+            writer.write_all(&[0])?;
+        }
+
+        if let Some(info) = &self.sampler_info {
+            writer.write_all(b"smpl")?;
+            write_u32(writer, (36 + 24 * info.loops.len()) as u32)?;
+            write_u32(writer, 0)?; // Manufacturer
+            write_u32(writer, 0)?; // Product
+            write_u32(writer, 1_000_000_000 / self.sample_rate.max(1))?; // Sample period in ns
+            write_u32(writer, info.unity_note)?;
+            write_u32(writer, info.pitch_fraction)?;
+            write_u32(writer, 0)?; // SMPTE format
+            write_u32(writer, 0)?; // SMPTE offset
+            write_u32(writer, info.loops.len() as u32)?;
+            write_u32(writer, 0)?; // Sampler data
+            for (i, value) in info.loops.iter().enumerate() {
+                write_u32(writer, i as u32)?; // Cue point ID
+                write_u32(writer, value.loop_type)?;
+                write_u32(writer, value.start)?;
+                write_u32(writer, value.end)?;
+                write_u32(writer, 0)?; // Fraction
+                write_u32(writer, value.play_count)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn write_u16<W: Write>(writer: &mut W, value: u16) -> Result<(), io::Error> {
+    writer.write_all(&value.to_le_bytes())
+}
+
+fn write_u32<W: Write>(writer: &mut W, value: u32) -> Result<(), io::Error> {
+    writer.write_all(&value.to_le_bytes())
+}
@@ -26,15 +26,18 @@ impl BiQuadFilter {
         self.y2 = 0.0;
     }
 
+    // This equation gives the Q value which makes the desired resonance peak.
+    // The error of the resultant peak height is less than 3%.
+    pub(crate) fn resonance_to_q(resonance: f32) -> f32 {
+        resonance - BiQuadFilter::RESONANCE_PEAK_OFFSET / (1.0 + 6.0 * (resonance - 1.0))
+    }
+
     pub(crate) fn set_low_pass_filter(&mut self, cutoff_frequency: f32, resonance: f32) {
         let sample_rate = crate::SAMPLE_RATE as f32;
         if cutoff_frequency < 0.499 * sample_rate {
             self.active = true;
 
-            // This equation gives the Q value which makes the desired resonance peak.
-            // The error of the resultant peak height is less than 3%.
-            let q =
-                resonance - BiQuadFilter::RESONANCE_PEAK_OFFSET / (1.0 + 6.0 * (resonance - 1.0));
+            let q = BiQuadFilter::resonance_to_q(resonance);
 
             let w = 2.0 * consts::PI * cutoff_frequency / sample_rate;
             let cosw = w.cos();
@@ -53,6 +56,64 @@ impl BiQuadFilter {
         }
     }
 
+    /// Sets a peaking filter, which boosts or cuts the band around the frequency.
+    pub(crate) fn set_peaking_filter(&mut self, frequency: f32, gain: f32, q: f32) {
+        let (cosw, alpha, a) = BiQuadFilter::get_eq_parameters(frequency, gain, q);
+
+        let b0 = 1.0 + alpha * a;
+        let b1 = -2.0 * cosw;
+        let b2 = 1.0 - alpha * a;
+        let a0 = 1.0 + alpha / a;
+        let a1 = -2.0 * cosw;
+        let a2 = 1.0 - alpha / a;
+
+        self.active = true;
+        self.set_coefficients(a0, a1, a2, b0, b1, b2);
+    }
+
+    /// Sets a low shelf filter, which boosts or cuts the frequencies below the frequency.
+    pub(crate) fn set_low_shelf_filter(&mut self, frequency: f32, gain: f32, q: f32) {
+        let (cosw, alpha, a) = BiQuadFilter::get_eq_parameters(frequency, gain, q);
+        let beta = 2.0 * a.sqrt() * alpha;
+
+        let b0 = a * ((a + 1.0) - (a - 1.0) * cosw + beta);
+        let b1 = 2.0 * a * ((a - 1.0) - (a + 1.0) * cosw);
+        let b2 = a * ((a + 1.0) - (a - 1.0) * cosw - beta);
+        let a0 = (a + 1.0) + (a - 1.0) * cosw + beta;
+        let a1 = -2.0 * ((a - 1.0) + (a + 1.0) * cosw);
+        let a2 = (a + 1.0) + (a - 1.0) * cosw - beta;
+
+        self.active = true;
+        self.set_coefficients(a0, a1, a2, b0, b1, b2);
+    }
+
+    /// Sets a high shelf filter, which boosts or cuts the frequencies above the frequency.
+    pub(crate) fn set_high_shelf_filter(&mut self, frequency: f32, gain: f32, q: f32) {
+        let (cosw, alpha, a) = BiQuadFilter::get_eq_parameters(frequency, gain, q);
+        let beta = 2.0 * a.sqrt() * alpha;
+
+        let b0 = a * ((a + 1.0) + (a - 1.0) * cosw + beta);
+        let b1 = -2.0 * a * ((a - 1.0) + (a + 1.0) * cosw);
+        let b2 = a * ((a + 1.0) + (a - 1.0) * cosw - beta);
+        let a0 = (a + 1.0) - (a - 1.0) * cosw + beta;
+        let a1 = 2.0 * ((a - 1.0) - (a + 1.0) * cosw);
+        let a2 = (a + 1.0) - (a - 1.0) * cosw - beta;
+
+        self.active = true;
+        self.set_coefficients(a0, a1, a2, b0, b1, b2);
+    }
+
+    // The equations are from the Audio EQ Cookbook by Robert Bristow-Johnson.
+    // The gain is in decibels.
+    fn get_eq_parameters(frequency: f32, gain: f32, q: f32) -> (f32, f32, f32) {
+        let sample_rate = crate::SAMPLE_RATE as f32;
+        let frequency = frequency.clamp(10.0, 0.49 * sample_rate);
+        let w = 2.0 * consts::PI * frequency / sample_rate;
+        let alpha = w.sin() / (2.0 * q.max(0.01));
+        let a = 10_f32.powf(gain / 40.0);
+        (w.cos(), alpha, a)
+    }
+
     pub(crate) fn render(&mut self, input: f32) -> f32 {
         let output = if self.active {
             self.a0 * input + self.a1 * self.x1 + self.a2 * self.x2
@@ -1,3 +1,15 @@
+use crate::BankSelectMode;
+use crate::preset_id::PresetId;
+use crate::pressure_routing::PressureRouting;
+
+// The bank select MSB values of the SFX kits and the drum kits of XG.
+const XG_SFX_KIT_BANK: u8 = 126;
+const XG_DRUM_KIT_BANK: u8 = 127;
+
+// The SFX set of GS, which plays the SFX kits of XG,
+// since SoundFonts have the drum kits of GS rather than XG.
+const GS_SFX_DRUM_SET: u8 = 56;
+
 #[derive(Debug, PartialEq, Eq, Default)]
 enum DataType {
     #[default]
@@ -6,20 +18,102 @@ enum DataType {
     Nrpn,
 }
 
+// The keys held in mono mode in the order they were pressed.
+// The array has a fixed size, so that pressing keys allocates no memory while rendering.
+#[derive(Debug)]
+struct KeyStack {
+    keys: [u8; 128],
+    len: usize,
+}
+
+impl Default for KeyStack {
+    fn default() -> Self {
+        Self {
+            keys: [0; 128],
+            len: 0,
+        }
+    }
+}
+
+impl KeyStack {
+    fn last(&self) -> Option<u8> {
+        self.keys[..self.len].last().copied()
+    }
+
+    // A key which is already in the stack is moved to the top.
+    fn push(&mut self, key: u8) {
+        self.remove(key);
+        if self.len < self.keys.len() {
+            self.keys[self.len] = key;
+            self.len += 1;
+        }
+    }
+
+    fn pop(&mut self) {
+        self.len = self.len.saturating_sub(1);
+    }
+
+    fn remove(&mut self, key: u8) {
+        if let Some(position) = self.keys[..self.len].iter().position(|&held| held == key) {
+            self.keys.copy_within(position + 1..self.len, position);
+            self.len -= 1;
+        }
+    }
+
+    fn clear(&mut self) {
+        self.len = 0;
+    }
+}
+
+// The pressure of each key, which has a fixed size like KeyStack.
+#[derive(Debug)]
+struct KeyPressure([u8; 128]);
+
+impl Default for KeyPressure {
+    fn default() -> Self {
+        Self([0; 128])
+    }
+}
+
 #[derive(Debug, Default)]
 pub(crate) struct Channel {
-    // XXX switch to u16
-    preset_id: i16,
+    // Kept by reset, as this is the assignment of the part.
+    percussion: bool,
+    // Kept by reset, as this is set by the application through the synthesizer.
+    bank_select_mode: BankSelectMode,
+    bank_msb: u8,
+    bank_lsb: u8,
+    program: u8,
 
     modulation: i16,
     volume: i16,
     pan: i16,
     expression: i16,
     hold_pedal: bool,
+    sostenuto_pedal: bool,
+    soft_pedal: bool,
+
+    portamento: bool,
+    portamento_time: i16,
+    // The key from which the next note slides, set by the portamento control (CC84).
+    portamento_control: Option<u8>,
+    // The key of the previous note, from which a new note slides.
+    last_key: Option<u8>,
+
+    mono: bool,
+    // The keys being held in mono mode, where the last one is sounding.
+    held_keys: KeyStack,
 
     reverb_send: u8,
     chorus_send: u8,
 
+    // The sound controllers (CC71-75), where 64 means no change.
+    harmonic_content: u8,
+    release_time: u8,
+    attack_time: u8,
+    brightness: u8,
+    decay_time: u8,
+
     rpn: i16,
     pitch_bend_range: i16,
     coarse_tune: i16,
@@ -27,6 +121,11 @@ pub(crate) struct Channel {
 
     pitch_bend: f32,
 
+    channel_pressure: u8,
+    poly_pressure: KeyPressure,
+    // Kept by reset, as this is set by the application.
+    pressure_routing: PressureRouting,
+
     last_data_type: DataType,
 }
 
@@ -48,11 +147,21 @@ macro_rules! set_coarse_fine {
 
 impl Channel {
     pub(crate) fn reset(&mut self) {
-        self.preset_id = 0;
+        self.bank_msb = 0;
+        self.bank_lsb = 0;
+        self.program = 0;
         self.volume = 100 << 7;
         self.pan = 64 << 7;
         self.reverb_send = 40;
         self.chorus_send = 0;
+        self.harmonic_content = 64;
+        self.release_time = 64;
+        self.attack_time = 64;
+        self.brightness = 64;
+        self.decay_time = 64;
+        self.portamento_time = 0;
+        self.last_key = None;
+        self.set_mono(false);
         self.pitch_bend_range = 2 << 7;
         self.coarse_tune = 0;
         self.fine_tune = 8192;
@@ -63,11 +172,36 @@ impl Channel {
         self.modulation = 0;
         self.expression = 127 << 7;
         self.hold_pedal = false;
+        self.sostenuto_pedal = false;
+        self.soft_pedal = false;
+        self.portamento = false;
+        self.portamento_control = None;
         self.rpn = -1;
         self.pitch_bend = 0.0;
+        self.channel_pressure = 0;
+        self.poly_pressure.0.fill(0);
+    }
+
+    pub(crate) fn set_percussion(&mut self, value: bool) {
+        self.percussion = value;
+    }
+
+    pub(crate) fn set_bank_select_mode(&mut self, value: BankSelectMode) {
+        self.bank_select_mode = value;
+    }
+
+    pub(crate) fn set_bank(&mut self, value: u8) {
+        self.bank_msb = value;
+    }
+
+    pub(crate) fn set_bank_lsb(&mut self, value: u8) {
+        self.bank_lsb = value;
+    }
+
+    pub(crate) fn set_patch(&mut self, value: u8) {
+        self.program = value;
     }
 
-    set_coarse_fine!(preset_id, set_bank, set_patch);
     set_coarse_fine!(modulation, set_modulation_coarse, set_modulation_fine);
     set_coarse_fine!(volume, set_volume_coarse, set_volume_fine);
     set_coarse_fine!(pan, set_pan_coarse, set_pan_fine);
@@ -77,6 +211,54 @@ impl Channel {
         self.hold_pedal = value >= 64;
     }
 
+    pub(crate) fn set_sostenuto_pedal(&mut self, value: u8) {
+        self.sostenuto_pedal = value >= 64;
+    }
+
+    pub(crate) fn set_soft_pedal(&mut self, value: u8) {
+        self.soft_pedal = value >= 64;
+    }
+
+    pub(crate) fn set_portamento(&mut self, value: u8) {
+        self.portamento = value >= 64;
+    }
+
+    set_coarse_fine!(
+        portamento_time,
+        set_portamento_time_coarse,
+        set_portamento_time_fine
+    );
+
+    pub(crate) fn set_portamento_control(&mut self, value: u8) {
+        self.portamento_control = Some(value & 0x7F);
+    }
+
+    pub(crate) fn set_mono(&mut self, value: bool) {
+        self.mono = value;
+        self.release_all_keys();
+    }
+
+    // Records a key pressed in mono mode.
+    pub(crate) fn press_key(&mut self, key: u8) {
+        self.held_keys.push(key);
+    }
+
+    // Records a key released in mono mode.
+    // If the key was sounding, gets the key held before it, which should sound again.
+    pub(crate) fn release_key(&mut self, key: u8) -> Option<u8> {
+        if self.held_keys.last() == Some(key) {
+            self.held_keys.pop();
+            self.held_keys.last()
+        } else {
+            self.held_keys.remove(key);
+            None
+        }
+    }
+
+    pub(crate) fn release_all_keys(&mut self) {
+        self.held_keys.clear();
+    }
+
     pub(crate) fn set_reverb_send(&mut self, value: u8) {
         self.reverb_send = value;
     }
@@ -85,6 +267,26 @@ impl Channel {
         self.chorus_send = value;
     }
 
+    pub(crate) fn set_harmonic_content(&mut self, value: u8) {
+        self.harmonic_content = value;
+    }
+
+    pub(crate) fn set_release_time(&mut self, value: u8) {
+        self.release_time = value;
+    }
+
+    pub(crate) fn set_attack_time(&mut self, value: u8) {
+        self.attack_time = value;
+    }
+
+    pub(crate) fn set_brightness(&mut self, value: u8) {
+        self.brightness = value;
+    }
+
+    pub(crate) fn set_decay_time(&mut self, value: u8) {
+        self.decay_time = value;
+    }
+
     set_coarse_fine!(rpn, set_rpn_coarse_, set_rpn_fine_);
     pub(crate) fn set_rpn_coarse(&mut self, value: u8) {
         self.set_rpn_coarse_(value);
@@ -137,8 +339,68 @@ impl Channel {
         self.pitch_bend = (1.0 / 8192.0) * (value - 8192) as f32;
     }
 
-    pub(crate) fn get_preset_id(&self) -> u16 {
-        self.preset_id as u16
+    pub(crate) fn set_channel_pressure(&mut self, value: u8) {
+        self.channel_pressure = value & 0x7F;
+    }
+
+    pub(crate) fn set_poly_pressure(&mut self, key: u8, value: u8) {
+        if let Some(pressure) = self.poly_pressure.0.get_mut(key as usize) {
+            *pressure = value & 0x7F;
+        }
+    }
+
+    pub(crate) fn set_pressure_routing(&mut self, value: PressureRouting) {
+        self.pressure_routing = value;
+    }
+
+    // The program change selects a drum kit on a percussion channel.
+    pub(crate) fn get_preset_id(&self) -> PresetId {
+        if self.percussion {
+            return PresetId::new(PresetId::PERCUSSION_BANK, self.program);
+        }
+
+        let msb = self.bank_msb as u16;
+        let lsb = self.bank_lsb as u16;
+        let bank = match self.bank_select_mode {
+            BankSelectMode::Gm => 0,
+            BankSelectMode::Gs => msb,
+            BankSelectMode::Xg => match self.bank_msb {
+                0 => lsb,
+                XG_SFX_KIT_BANK => {
+                    return PresetId::new(PresetId::PERCUSSION_BANK, GS_SFX_DRUM_SET);
+                }
+                XG_DRUM_KIT_BANK => PresetId::PERCUSSION_BANK,
+                _ => msb,
+            },
+            BankSelectMode::Mma => (msb << 7) | lsb,
+        };
+        PresetId::new(bank, self.program)
+    }
+
+    // The preset tried before the GM fallback of the sound source if the selected one is missing.
+    pub(crate) fn get_variation_fallback(&self) -> Option<PresetId> {
+        if self.is_percussion() {
+            return None;
+        }
+
+        match self.bank_select_mode {
+            // The sub-capital tone of GS.
+            BankSelectMode::Gs if self.bank_msb & 7 != 0 => {
+                Some(PresetId::new((self.bank_msb & !7) as u16, self.program))
+            }
+            BankSelectMode::Mma if self.bank_msb != 0 => {
+                Some(PresetId::new(self.bank_msb as u16, self.program))
+            }
+            _ => None,
+        }
+    }
+
+    // The channel is a drum part if it is assigned to be,
+    // or if it selects the drum kits or the SFX kits of XG.
+    pub(crate) fn is_percussion(&self) -> bool {
+        self.percussion
+            || (self.bank_select_mode == BankSelectMode::Xg
+                && matches!(self.bank_msb, XG_SFX_KIT_BANK | XG_DRUM_KIT_BANK))
     }
 
     pub(crate) fn get_modulation(&self) -> f32 {
@@ -157,10 +419,43 @@ impl Channel {
         (1.0 / 16383.0) * self.expression as f32
     }
 
+    pub(crate) fn is_mono(&self) -> bool {
+        self.mono
+    }
+
     pub(crate) fn get_hold_pedal(&self) -> bool {
         self.hold_pedal
     }
 
+    // The time of the portamento in seconds, which is from 10 ms to 10 s except for 0.
+    pub(crate) fn get_portamento_time(&self) -> f32 {
+        if self.portamento_time == 0 {
+            0.0
+        } else {
+            0.01 * 1000_f32.powf((1.0 / 16383.0) * self.portamento_time as f32)
+        }
+    }
+
+    // Records the key of a new note, and gets the key from which the note should slide.
+    // The portamento control takes effect for one note even if the portamento is off.
+    pub(crate) fn take_portamento_source(&mut self, key: u8) -> Option<u8> {
+        let source = match self.portamento_control.take() {
+            Some(source) => Some(source),
+            None if self.portamento => self.last_key,
+            None => None,
+        };
+        self.last_key = Some(key);
+        source.filter(|&source| source != key)
+    }
+
+    pub(crate) fn get_sostenuto_pedal(&self) -> bool {
+        self.sostenuto_pedal
+    }
+
+    pub(crate) fn get_soft_pedal(&self) -> bool {
+        self.soft_pedal
+    }
+
     pub(crate) fn get_reverb_send(&self) -> f32 {
         (1.0 / 127.0) * self.reverb_send as f32
     }
@@ -169,6 +464,46 @@ impl Channel {
         (1.0 / 127.0) * self.chorus_send as f32
     }
 
+    // The resonance added to the filter in decibels.
+    pub(crate) fn get_harmonic_content(&self) -> f32 {
+        0.3 * (self.harmonic_content as i32 - 64) as f32
+    }
+
+    // The multiplier of the release time of the volume envelope.
+    pub(crate) fn get_release_time_factor(&self) -> f32 {
+        Channel::get_time_factor(self.release_time)
+    }
+
+    // The multiplier of the attack time of the volume envelope.
+    pub(crate) fn get_attack_time_factor(&self) -> f32 {
+        Channel::get_time_factor(self.attack_time)
+    }
+
+    // The change of the filter cutoff in cents.
+    pub(crate) fn get_brightness(&self) -> f32 {
+        75.0 * (self.brightness as i32 - 64) as f32
+    }
+
+    // The multiplier of the decay time of the volume envelope.
+    pub(crate) fn get_decay_time_factor(&self) -> f32 {
+        Channel::get_time_factor(self.decay_time)
+    }
+
+    // Each step of 16 doubles or halves the time, so the range is from x1/16 to x16.
+    fn get_time_factor(value: u8) -> f32 {
+        2_f32.powf((value as i32 - 64) as f32 / 16.0)
+    }
+
+    // The pressure on the key from 0 to 1, which is the higher of the channel and the key.
+    pub(crate) fn get_pressure(&self, key: i32) -> f32 {
+        let poly_pressure = self.poly_pressure.0.get(key as usize).copied().unwrap_or(0);
+        (1.0 / 127.0) * self.channel_pressure.max(poly_pressure) as f32
+    }
+
+    pub(crate) fn get_pressure_routing(&self) -> &PressureRouting {
+        &self.pressure_routing
+    }
+
     pub(crate) fn get_pitch_bend_range(&self) -> f32 {
         (self.pitch_bend_range >> 7) as f32 + 0.01 * (self.pitch_bend_range & 0x7F) as f32
     }
@@ -0,0 +1,145 @@
+use crate::effect::Effect;
+use crate::soundfont_math::*;
+
+/// Reduces the dynamic range by turning down the signal above the threshold.
+/// The gain is computed from the louder of the left and right channels,
+/// so that the stereo image does not move.
+#[derive(Debug)]
+pub struct Compressor {
+    threshold: f32,
+    ratio: f32,
+    attack: f32,
+    release: f32,
+    makeup: f32,
+
+    attack_coefficient: f32,
+    release_coefficient: f32,
+    makeup_gain: f32,
+
+    envelope: f32,
+    gain_reduction: f32,
+}
+
+impl Compressor {
+    /// Creates a new compressor.
+    ///
+    /// # Arguments
+    ///
+    /// * `threshold` - The level above which the signal is compressed in decibels.
+    /// * `ratio` - The ratio of the input level change to the output level change above the threshold.
+    /// * `attack` - The time to react to a louder signal in seconds.
+    /// * `release` - The time to recover from the compression in seconds.
+    /// * `makeup` - The gain applied after the compression in decibels.
+    pub fn new(threshold: f32, ratio: f32, attack: f32, release: f32, makeup: f32) -> Self {
+        let mut compressor = Self {
+            threshold,
+            ratio: ratio.max(1.0),
+            attack: 0.0,
+            release: 0.0,
+            makeup: 0.0,
+            attack_coefficient: 0.0,
+            release_coefficient: 0.0,
+            makeup_gain: 1.0,
+            envelope: 0.0,
+            gain_reduction: 0.0,
+        };
+        compressor.set_attack(attack);
+        compressor.set_release(release);
+        compressor.set_makeup(makeup);
+        compressor
+    }
+
+    /// Gets the threshold in decibels.
+    pub fn get_threshold(&self) -> f32 {
+        self.threshold
+    }
+
+    /// Sets the threshold in decibels.
+    pub fn set_threshold(&mut self, value: f32) {
+        self.threshold = value;
+    }
+
+    /// Gets the compression ratio.
+    pub fn get_ratio(&self) -> f32 {
+        self.ratio
+    }
+
+    /// Sets the compression ratio. Values below 1 are treated as 1.
+    pub fn set_ratio(&mut self, value: f32) {
+        self.ratio = value.max(1.0);
+    }
+
+    /// Gets the attack time in seconds.
+    pub fn get_attack(&self) -> f32 {
+        self.attack
+    }
+
+    /// Sets the attack time in seconds.
+    pub fn set_attack(&mut self, value: f32) {
+        self.attack = value;
+        self.attack_coefficient = time_to_coefficient(value);
+    }
+
+    /// Gets the release time in seconds.
+    pub fn get_release(&self) -> f32 {
+        self.release
+    }
+
+    /// Sets the release time in seconds.
+    pub fn set_release(&mut self, value: f32) {
+        self.release = value;
+        self.release_coefficient = time_to_coefficient(value);
+    }
+
+    /// Gets the makeup gain in decibels.
+    pub fn get_makeup(&self) -> f32 {
+        self.makeup
+    }
+
+    /// Sets the makeup gain in decibels.
+    pub fn set_makeup(&mut self, value: f32) {
+        self.makeup = value;
+        self.makeup_gain = decibels_to_linear(value);
+    }
+
+    /// Gets the current gain reduction in decibels, which is useful for metering.
+    pub fn get_gain_reduction(&self) -> f32 {
+        self.gain_reduction
+    }
+}
+
+impl Effect for Compressor {
+    fn process(&mut self, left: f32, right: f32) -> (f32, f32) {
+        let level = left.abs().max(right.abs());
+        let coefficient = if level > self.envelope {
+            self.attack_coefficient
+        } else {
+            self.release_coefficient
+        };
+        self.envelope = level + coefficient * (self.envelope - level);
+
+        let over = linear_to_decibels(self.envelope.max(1.0E-6)) - self.threshold;
+        self.gain_reduction = if over > 0.0 {
+            over * (1.0 - 1.0 / self.ratio)
+        } else {
+            0.0
+        };
+
+        let gain = self.makeup_gain * decibels_to_linear(-self.gain_reduction);
+        (gain * left, gain * right)
+    }
+
+    fn reset(&mut self) {
+        self.envelope = 0.0;
+        self.gain_reduction = 0.0;
+    }
+}
+
+// Gets the coefficient of a one-pole smoother which reaches 63% of the target in the time.
+fn time_to_coefficient(time: f32) -> f32 {
+    if time > 0.0 {
+        (-1.0 / (time * crate::SAMPLE_RATE as f32)).exp()
+    } else {
+        0.0
+    }
+}
@@ -0,0 +1,126 @@
+use std::any::Any;
+use std::fmt;
+
+/// An audio processor which can be inserted into the signal path of the synthesizer.
+/// Effects process stereo frames at the sample rate of the synthesizer.
+pub trait Effect: Any + Send {
+    /// Processes a stereo frame and returns the output frame.
+    fn process(&mut self, left: f32, right: f32) -> (f32, f32);
+
+    /// Clears the internal state, such as delay lines and envelope followers.
+    fn reset(&mut self) {}
+}
+
+/// An ordered chain of effects.
+/// The output of each effect is the input of the next one.
+/// An empty or bypassed chain passes the signal through unchanged.
+#[derive(Default)]
+pub struct EffectChain {
+    effects: Vec<Box<dyn Effect>>,
+    bypass: bool,
+}
+
+impl EffectChain {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds an effect to the end of the chain.
+    pub fn push<E: Effect>(&mut self, effect: E) {
+        self.effects.push(Box::new(effect));
+    }
+
+    /// Inserts an effect at the given position in the chain.
+    ///
+    /// # Arguments
+    ///
+    /// * `index` - The position of the new effect.
+    /// * `effect` - The effect to insert.
+    pub fn insert<E: Effect>(&mut self, index: usize, effect: E) {
+        self.effects.insert(index, Box::new(effect));
+    }
+
+    /// Removes the effect at the given position, and returns it.
+    /// Returns `None` if the position is out of range.
+    pub fn remove(&mut self, index: usize) -> Option<Box<dyn Effect>> {
+        if index < self.effects.len() {
+            Some(self.effects.remove(index))
+        } else {
+            None
+        }
+    }
+
+    /// Removes all the effects.
+    pub fn clear(&mut self) {
+        self.effects.clear();
+    }
+
+    /// Gets the number of effects in the chain.
+    pub fn len(&self) -> usize {
+        self.effects.len()
+    }
+
+    /// Returns `true` if the chain has no effects.
+    pub fn is_empty(&self) -> bool {
+        self.effects.is_empty()
+    }
+
+    /// Gets the effect at the given position.
+    /// Returns `None` if the position is out of range or the effect is not of the type `E`.
+    pub fn get<E: Effect>(&self, index: usize) -> Option<&E> {
+        let effect: &dyn Any = self.effects.get(index)?.as_ref();
+        effect.downcast_ref::<E>()
+    }
+
+    /// Gets the effect at the given position for modification, such as changing its parameters.
+    /// Returns `None` if the position is out of range or the effect is not of the type `E`.
+    pub fn get_mut<E: Effect>(&mut self, index: usize) -> Option<&mut E> {
+        let effect: &mut dyn Any = self.effects.get_mut(index)?.as_mut();
+        effect.downcast_mut::<E>()
+    }
+
+    /// Returns `true` if the chain is bypassed.
+    pub fn is_bypassed(&self) -> bool {
+        self.bypass
+    }
+
+    /// Bypasses the chain without removing the effects.
+    /// The effects keep their state while the chain is bypassed.
+    pub fn set_bypass(&mut self, value: bool) {
+        self.bypass = value;
+    }
+
+    /// Processes a stereo frame through all the effects in order.
+    pub fn process(&mut self, left: f32, right: f32) -> (f32, f32) {
+        if self.bypass {
+            return (left, right);
+        }
+
+        self.effects
+            .iter_mut()
+            .fold((left, right), |(left, right), effect| {
+                effect.process(left, right)
+            })
+    }
+
+    /// Clears the internal state of all the effects.
+    pub fn reset(&mut self) {
+        for effect in &mut self.effects {
+            effect.reset();
+        }
+    }
+
+    /// Returns `true` if the chain changes the signal.
+    pub(crate) fn is_active(&self) -> bool {
+        !self.bypass && !self.effects.is_empty()
+    }
+}
+
+impl fmt::Debug for EffectChain {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("EffectChain")
+            .field("len", &self.effects.len())
+            .field("bypass", &self.bypass)
+            .finish()
+    }
+}
@@ -0,0 +1,117 @@
+use crate::bi_quad_filter::BiQuadFilter;
+use crate::effect::Effect;
+
+/// Specifies the shape of an equalizer band.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EqBandType {
+    /// Boosts or cuts the frequencies below the frequency of the band.
+    LowShelf,
+    /// Boosts or cuts the frequencies around the frequency of the band.
+    Peak,
+    /// Boosts or cuts the frequencies above the frequency of the band.
+    HighShelf,
+}
+
+/// A band of `Equalizer`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct EqBand {
+    pub band_type: EqBandType,
+    /// The center or corner frequency in Hz.
+    pub frequency: f32,
+    /// The gain in decibels. A negative value cuts the band.
+    pub gain: f32,
+    /// The Q factor, which narrows the band as it increases.
+    pub q: f32,
+}
+
+impl EqBand {
+    pub fn new(band_type: EqBandType, frequency: f32, gain: f32, q: f32) -> Self {
+        Self {
+            band_type,
+            frequency,
+            gain,
+            q,
+        }
+    }
+}
+
+/// A parametric equalizer with any number of bands.
+#[derive(Debug, Default)]
+pub struct Equalizer {
+    bands: Vec<EqBand>,
+    // The filters for the left and right channels of each band.
+    filters: Vec<[BiQuadFilter; 2]>,
+}
+
+impl Equalizer {
+    /// Creates a new equalizer.
+    ///
+    /// # Arguments
+    ///
+    /// * `bands` - The bands of the equalizer, which are applied in order.
+    pub fn new(bands: Vec<EqBand>) -> Self {
+        let mut equalizer = Self::default();
+        for band in bands {
+            equalizer.add_band(band);
+        }
+        equalizer
+    }
+
+    /// Gets the bands of the equalizer.
+    pub fn get_bands(&self) -> &[EqBand] {
+        &self.bands[..]
+    }
+
+    /// Adds a band to the equalizer.
+    pub fn add_band(&mut self, band: EqBand) {
+        self.bands.push(band);
+        self.filters.push(Default::default());
+        self.update_filters(self.bands.len() - 1);
+    }
+
+    /// Changes the band at the given position.
+    /// The state of the filters is kept, so that the change is smooth.
+    /// Panics if the position is out of range.
+    pub fn set_band(&mut self, index: usize, band: EqBand) {
+        self.bands[index] = band;
+        self.update_filters(index);
+    }
+
+    /// Removes the band at the given position.
+    /// Panics if the position is out of range.
+    pub fn remove_band(&mut self, index: usize) {
+        self.bands.remove(index);
+        self.filters.remove(index);
+    }
+
+    fn update_filters(&mut self, index: usize) {
+        let band = self.bands[index];
+        for filter in &mut self.filters[index] {
+            match band.band_type {
+                EqBandType::LowShelf => {
+                    filter.set_low_shelf_filter(band.frequency, band.gain, band.q)
+                }
+                EqBandType::Peak => filter.set_peaking_filter(band.frequency, band.gain, band.q),
+                EqBandType::HighShelf => {
+                    filter.set_high_shelf_filter(band.frequency, band.gain, band.q)
+                }
+            }
+        }
+    }
+}
+
+impl Effect for Equalizer {
+    fn process(&mut self, mut left: f32, mut right: f32) -> (f32, f32) {
+        for [filter_left, filter_right] in &mut self.filters {
+            left = filter_left.render(left);
+            right = filter_right.render(right);
+        }
+        (left, right)
+    }
+
+    fn reset(&mut self) {
+        for filter in self.filters.iter_mut().flatten() {
+            filter.clear_buffer();
+        }
+    }
+}
@@ -2,25 +2,54 @@ pub mod soundfont_math;
 
 mod bi_quad_filter;
 mod channel;
+mod compressor;
+mod effect;
+mod equalizer;
 mod lfo;
+mod limiter;
 mod modulation_envelope;
 mod oscillator;
+mod overdrive;
+mod portamento;
+mod preset_id;
+mod pressure_routing;
+mod procedural_drums;
+mod procedural_source;
+mod signal_generator;
+mod soft_clipper;
+mod sound_source_stack;
+mod state_variable_filter;
 mod synthesizer;
+mod tremolo;
 mod voice;
 mod volume_envelope;
+mod wave_data;
 
 // XXX chorus
 // XXX echo
 // XXX delay
 mod reverb;
 
+pub use self::compressor::Compressor;
+pub use self::effect::{Effect, EffectChain};
+pub use self::equalizer::{EqBand, EqBandType, Equalizer};
+pub use self::limiter::Limiter;
 pub use self::oscillator::View;
+pub use self::overdrive::Overdrive;
+pub use self::preset_id::{PresetDescriptor, PresetId};
+pub use self::pressure_routing::PressureRouting;
+pub use self::procedural_source::{ProceduralSound, ProceduralSource};
+pub use self::signal_generator::{GeneratorSlot, SignalGenerator};
+pub use self::soft_clipper::SoftClipper;
+pub use self::sound_source_stack::{SoundSourceLayer, SoundSourceStack};
 pub use self::synthesizer::{Sound, SoundSource, Synthesizer};
+pub use self::tremolo::Tremolo;
+pub use self::wave_data::{WaveData, WaveView};
 
 pub const SAMPLE_RATE: i32 = 44100;
 
 /// Specifies how the sample loops during playback.
-#[derive(Debug, PartialEq, Eq, Default)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
 pub enum LoopMode {
     /// The sample will be played without loop.
     #[default]
@@ -31,6 +60,44 @@ pub enum LoopMode {
     LoopUntilNoteOff,
 }
 
+/// Specifies how the bank select messages (CC0 and CC32) choose the bank of the preset.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum BankSelectMode {
+    /// The bank select is ignored, and the bank 0 is always used.
+    Gm,
+    /// The MSB selects the bank, and the LSB is ignored.
+    /// A missing variation falls back to the first one in its group of 8 banks.
+    #[default]
+    Gs,
+    /// The LSB selects the bank for the MSB 0, and the MSB 127 selects the drum kits.
+    /// The MSB 126 selects the SFX kits, which are played by the SFX set of GS (128:56).
+    /// Both make the channel a drum part. Other MSB values select the bank.
+    Xg,
+    /// The MSB and the LSB select the bank as a 14-bit number.
+    /// A missing bank falls back to the one selected by the MSB alone.
+    Mma,
+}
+
+/// Specifies the filter applied to each voice.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FilterType {
+    /// The 12 dB/oct resonant low-pass filter defined by the SoundFont format.
+    /// Its cutoff follows the modulation at most an octave per sample, which avoids pop noise
+    /// but smooths fast sweeps. This keeps the sound of existing SoundFonts.
+    #[default]
+    LowPass,
+    /// A 12 dB/oct resonant low-pass filter which follows fast cutoff changes smoothly.
+    StateVariableLowPass,
+    /// A 12 dB/oct resonant high-pass filter.
+    HighPass,
+    /// A band-pass filter whose bandwidth narrows as the resonance increases.
+    BandPass,
+    /// A filter which removes the band around the cutoff frequency.
+    Notch,
+    /// A 24 dB/oct resonant low-pass filter.
+    LowPass24,
+}
+
 #[derive(Debug, Clone, Copy, Default, Ord, PartialOrd, Eq, PartialEq)]
 #[allow(clippy::upper_case_acronyms)]
 pub(crate) enum EnvelopeStage {
@@ -0,0 +1,170 @@
+use std::collections::VecDeque;
+
+use crate::effect::Effect;
+use crate::soundfont_math::decibels_to_linear;
+
+/// A look-ahead brickwall limiter.
+/// The signal is delayed by the look-ahead time, so that the gain can go down
+/// before a peak arrives and the output never exceeds the ceiling.
+#[derive(Debug)]
+pub struct Limiter {
+    ceiling: f32,
+    release: f32,
+
+    ceiling_gain: f32,
+    release_coefficient: f32,
+
+    // The delayed input.
+    left_delay: Vec<f32>,
+    right_delay: Vec<f32>,
+    // The gains smoothed by the moving average over the look-ahead time.
+    gains: Vec<f32>,
+    gain_sum: f64,
+    position: usize,
+
+    // The minimum of the required gains over the look-ahead time is found with a monotonic queue.
+    minimum_gains: VecDeque<(u64, f32)>,
+    sample_count: u64,
+    released_gain: f32,
+    current_gain: f32,
+}
+
+impl Limiter {
+    /// Creates a new limiter.
+    ///
+    /// # Arguments
+    ///
+    /// * `ceiling` - The maximum output level in decibels below the full scale.
+    /// * `lookahead` - The look-ahead time in seconds, which is also the latency of the limiter.
+    /// * `release` - The time to recover from the gain reduction in seconds.
+    pub fn new(ceiling: f32, lookahead: f32, release: f32) -> Self {
+        let length = ((lookahead * crate::SAMPLE_RATE as f32) as usize).max(1);
+
+        let mut limiter = Self {
+            ceiling: 0.0,
+            release: 0.0,
+            ceiling_gain: 1.0,
+            release_coefficient: 0.0,
+            left_delay: vec![0.0; length],
+            right_delay: vec![0.0; length],
+            gains: vec![1.0; length],
+            gain_sum: length as f64,
+            position: 0,
+            minimum_gains: VecDeque::with_capacity(length + 1),
+            sample_count: 0,
+            released_gain: 1.0,
+            current_gain: 1.0,
+        };
+        limiter.set_ceiling(ceiling);
+        limiter.set_release(release);
+        limiter
+    }
+
+    /// Gets the maximum output level in decibels.
+    pub fn get_ceiling(&self) -> f32 {
+        self.ceiling
+    }
+
+    /// Sets the maximum output level in decibels.
+    pub fn set_ceiling(&mut self, value: f32) {
+        self.ceiling = value;
+        self.ceiling_gain = decibels_to_linear(value);
+    }
+
+    /// Gets the release time in seconds.
+    pub fn get_release(&self) -> f32 {
+        self.release
+    }
+
+    /// Sets the release time in seconds.
+    pub fn set_release(&mut self, value: f32) {
+        self.release = value;
+        self.release_coefficient = if value > 0.0 {
+            (-1.0 / (value * crate::SAMPLE_RATE as f32)).exp()
+        } else {
+            0.0
+        };
+    }
+
+    /// Gets the delay of the output in samples.
+    pub fn get_latency(&self) -> usize {
+        self.left_delay.len() - 1
+    }
+
+    /// Gets the current gain reduction in decibels, which is useful for metering.
+    pub fn get_gain_reduction(&self) -> f32 {
+        -20.0 * self.current_gain.max(1.0E-6).log10()
+    }
+}
+
+impl Effect for Limiter {
+    fn process(&mut self, left: f32, right: f32) -> (f32, f32) {
+        let length = self.gains.len();
+
+        let peak = left.abs().max(right.abs());
+        let required_gain = if peak > self.ceiling_gain {
+            self.ceiling_gain / peak
+        } else {
+            1.0
+        };
+
+        // Hold the lowest gain for the look-ahead time.
+        while let Some(&(_, gain)) = self.minimum_gains.back() {
+            if gain < required_gain {
+                break;
+            }
+            self.minimum_gains.pop_back();
+        }
+        self.minimum_gains
+            .push_back((self.sample_count, required_gain));
+        while let Some(&(index, _)) = self.minimum_gains.front() {
+            if index + length as u64 > self.sample_count {
+                break;
+            }
+            self.minimum_gains.pop_front();
+        }
+        let held_gain = self.minimum_gains.front().map_or(1.0, |&(_, gain)| gain);
+        self.sample_count += 1;
+
+        // The gain goes down immediately and comes back slowly.
+        self.released_gain = if held_gain < self.released_gain {
+            held_gain
+        } else {
+            held_gain + self.release_coefficient * (self.released_gain - held_gain)
+        };
+
+        // The moving average makes the gain go down smoothly within the look-ahead time.
+        // It never exceeds the held gain at the time the peak comes out of the delay.
+        self.gain_sum += (self.released_gain - self.gains[self.position]) as f64;
+        self.gains[self.position] = self.released_gain;
+        self.current_gain = (self.gain_sum / length as f64) as f32;
+
+        // The oldest sample in the buffers is the output.
+        let next = (self.position + 1) % length;
+        self.left_delay[self.position] = left;
+        self.right_delay[self.position] = right;
+        let output_left = self.left_delay[next];
+        let output_right = self.right_delay[next];
+        self.position = next;
+
+        // Guard against the rounding error of the moving average.
+        let output_left =
+            (self.current_gain * output_left).clamp(-self.ceiling_gain, self.ceiling_gain);
+        let output_right =
+            (self.current_gain * output_right).clamp(-self.ceiling_gain, self.ceiling_gain);
+        (output_left, output_right)
+    }
+
+    fn reset(&mut self) {
+        let length = self.gains.len();
+        self.left_delay.fill(0.0);
+        self.right_delay.fill(0.0);
+        self.gains.fill(1.0);
+        self.gain_sum = length as f64;
+        self.position = 0;
+        self.minimum_gains.clear();
+        self.sample_count = 0;
+        self.released_gain = 1.0;
+        self.current_gain = 1.0;
+    }
+}
@@ -1,5 +1,6 @@
 use crate::LoopMode;
-use std::{ops::Index, sync::Arc};
+use crate::wave_data::{WaveData, WaveView};
+use std::{ops::Index, ops::Range, sync::Arc};
 
 // XXX Almost all of the samples fit in 32kb
 #[derive(Debug, Clone)]
@@ -24,6 +25,63 @@ impl<T> Index<usize> for View<T> {
     }
 }
 
+// The sample played by a voice. The kind of the data is resolved when the voice starts,
+// so that rendering reads the samples from a plain slice.
+#[derive(Debug)]
+enum Samples {
+    Decoded(Arc<[i16]>, Range<usize>),
+    // The range is in bytes.
+    Encoded(Arc<[u8]>, Range<usize>),
+    Static(&'static [u8]),
+}
+
+impl Samples {
+    // Returns `None` if the sample is out of the bounds of the data.
+    fn new(view: WaveView) -> Option<Self> {
+        if view.start > view.end || view.end > view.data.len() {
+            return None;
+        }
+        let samples = match view.data {
+            WaveData::Samples(data) => Samples::Decoded(data, view.start..view.end),
+            WaveData::Bytes { data, offset, .. } => {
+                Samples::Encoded(data, offset + 2 * view.start..offset + 2 * view.end)
+            }
+            WaveData::Static(data) => Samples::Static(&data[2 * view.start..2 * view.end]),
+        };
+        Some(samples)
+    }
+}
+
+// 16-bit samples, either decoded or as little-endian bytes.
+trait SampleSlice {
+    fn sample_count(&self) -> usize;
+    fn sample(&self, index: usize) -> i64;
+}
+
+impl SampleSlice for [i16] {
+    #[inline]
+    fn sample_count(&self) -> usize {
+        self.len()
+    }
+
+    #[inline]
+    fn sample(&self, index: usize) -> i64 {
+        self[index] as i64
+    }
+}
+
+impl SampleSlice for [u8] {
+    #[inline]
+    fn sample_count(&self) -> usize {
+        self.len() / 2
+    }
+
+    #[inline]
+    fn sample(&self, index: usize) -> i64 {
+        i16::from_le_bytes([self[2 * index], self[2 * index + 1]]) as i64
+    }
+}
+
 // In this class, fixed-point numbers are used for speed-up.
 // A fixed-point number is expressed by Int64, whose lower 24 bits represent the fraction part,
 // and the rest represent the integer part.
@@ -31,7 +89,7 @@ impl<T> Index<usize> for View<T> {
 
 #[derive(Debug, Default)]
 pub(crate) struct Oscillator {
-    data: Option<View<i16>>,
+    data: Option<Samples>,
     loop_mode: LoopMode,
     sample_sample_rate: i32,
     start_loop: i32,
@@ -44,6 +102,10 @@ pub(crate) struct Oscillator {
     looping: bool,
 
     position_fp: i64,
+
+    // The pitch ratio is computed again only when the pitch changes.
+    last_pitch: f32,
+    pitch_ratio_fp: i64,
 }
 
 const FRAC_BITS: i32 = 24;
@@ -54,7 +116,7 @@ impl Oscillator {
     #[allow(clippy::too_many_arguments)]
     pub(crate) fn start(
         &mut self,
-        data: View<i16>,
+        data: WaveView,
         loop_mode: LoopMode,
         sample_rate: i32,
         start_loop: i32,
@@ -62,7 +124,8 @@ impl Oscillator {
         root_key: i32,
         fine_tune: i32,
     ) {
-        self.data = Some(data);
+        let length = data.end.saturating_sub(data.start);
+        self.data = Samples::new(data);
         self.loop_mode = loop_mode;
         self.sample_sample_rate = sample_rate;
         self.start_loop = start_loop;
@@ -71,8 +134,13 @@ impl Oscillator {
 
         self.tune = 0.01 * fine_tune as f32;
         self.sample_rate_ratio = sample_rate as f32 / crate::SAMPLE_RATE as f32;
-        self.looping = self.loop_mode != LoopMode::NoLoop;
+        // A loop outside of the sample cannot be played, so the sample is played without looping.
+        self.looping = self.loop_mode != LoopMode::NoLoop
+            && 0 <= start_loop
+            && start_loop < end_loop
+            && end_loop as usize <= length;
         self.position_fp = 0_i64 << FRAC_BITS;
+        self.last_pitch = f32::NAN;
     }
 
     pub(crate) fn release(&mut self) {
@@ -82,9 +150,19 @@ impl Oscillator {
     }
 
     pub(crate) fn render(&mut self, pitch: f32) -> Option<f32> {
-        self.data.as_ref()?;
-        let data = self.data.as_ref().unwrap();
+        // The data is moved out during rendering, so that the samples can be borrowed
+        // while the state of the oscillator changes.
+        let data = self.data.take()?;
+        let value = match &data {
+            Samples::Decoded(data, range) => self.render_samples(&data[range.clone()], pitch),
+            Samples::Encoded(data, range) => self.render_samples(&data[range.clone()], pitch),
+            Samples::Static(data) => self.render_samples(*data, pitch),
+        };
+        self.data = Some(data);
+        value
+    }
 
+    fn render_samples<S: SampleSlice + ?Sized>(&mut self, data: &S, pitch: f32) -> Option<f32> {
         // XXX Improve this algorithm e.g. windowed sinc or gaussian
         let (index1, index2) = if self.looping {
             let end_loop_fp = (self.end_loop as i64) << FRAC_BITS;
@@ -92,7 +170,10 @@ impl Oscillator {
             let loop_length_fp = loop_length << FRAC_BITS;
 
             if self.position_fp >= end_loop_fp {
-                self.position_fp -= loop_length_fp;
+                // A high pitch may step over more than one loop.
+                let start_loop_fp = (self.start_loop as i64) << FRAC_BITS;
+                self.position_fp =
+                    start_loop_fp + (self.position_fp - start_loop_fp) % loop_length_fp;
             }
 
             let index1 = (self.position_fp >> FRAC_BITS) as usize;
@@ -103,20 +184,31 @@ impl Oscillator {
             (index1, index2)
         } else {
             let index = (self.position_fp >> FRAC_BITS) as usize;
-            if index >= data.len() {
+            if index >= data.sample_count() {
                 return None;
             }
-            (index, index + 1)
+            // The data after the end of the sample is not guaranteed to exist.
+            (index, (index + 1).min(data.sample_count() - 1))
         };
 
-        let pitch_change = (pitch - self.root_key as f32) + self.tune;
-        let pitch_ratio = (self.sample_rate_ratio * 2_f32.powf(pitch_change / 12.0)) as f64;
-        let pitch_ratio_fp = (FRAC_UNIT as f64 * pitch_ratio) as i64;
+        if pitch != self.last_pitch {
+            let pitch_change = (pitch - self.root_key as f32) + self.tune;
+            let pitch_ratio = (self.sample_rate_ratio * 2_f32.powf(pitch_change / 12.0)) as f64;
+            self.pitch_ratio_fp = (FRAC_UNIT as f64 * pitch_ratio) as i64;
+            self.last_pitch = pitch;
+        }
 
-        let x1 = data[index1] as i64;
-        let x2 = data[index2] as i64;
+        let x1 = data.sample(index1);
         let a_fp = self.position_fp & (FRAC_UNIT - 1);
-        self.position_fp += pitch_ratio_fp;
+        self.position_fp = self.position_fp.saturating_add(self.pitch_ratio_fp);
+
+        // A sample at the output rate played at its root key steps by whole samples,
+        // which needs no interpolation. This is the case for resampled SoundFonts.
+        if a_fp == 0 {
+            return Some(FP_TO_SAMPLE * (x1 << FRAC_BITS) as f32);
+        }
+
+        let x2 = data.sample(index2);
         Some(FP_TO_SAMPLE * ((x1 << FRAC_BITS) + a_fp * (x2 - x1)) as f32)
     }
 }
@@ -0,0 +1,63 @@
+use crate::effect::Effect;
+use crate::soundfont_math::decibels_to_linear;
+
+/// A distortion which saturates the signal smoothly like an overdriven amplifier.
+#[derive(Debug)]
+pub struct Overdrive {
+    drive: f32,
+    level: f32,
+
+    drive_gain: f32,
+    level_gain: f32,
+}
+
+impl Overdrive {
+    /// Creates a new overdrive.
+    ///
+    /// # Arguments
+    ///
+    /// * `drive` - The gain before the saturation in decibels.
+    /// * `level` - The gain after the saturation in decibels.
+    pub fn new(drive: f32, level: f32) -> Self {
+        let mut overdrive = Self {
+            drive: 0.0,
+            level: 0.0,
+            drive_gain: 1.0,
+            level_gain: 1.0,
+        };
+        overdrive.set_drive(drive);
+        overdrive.set_level(level);
+        overdrive
+    }
+
+    /// Gets the gain before the saturation in decibels.
+    pub fn get_drive(&self) -> f32 {
+        self.drive
+    }
+
+    /// Sets the gain before the saturation in decibels.
+    pub fn set_drive(&mut self, value: f32) {
+        self.drive = value;
+        self.drive_gain = decibels_to_linear(value);
+    }
+
+    /// Gets the gain after the saturation in decibels.
+    pub fn get_level(&self) -> f32 {
+        self.level
+    }
+
+    /// Sets the gain after the saturation in decibels.
+    pub fn set_level(&mut self, value: f32) {
+        self.level = value;
+        self.level_gain = decibels_to_linear(value);
+    }
+}
+
+impl Effect for Overdrive {
+    fn process(&mut self, left: f32, right: f32) -> (f32, f32) {
+        (
+            self.level_gain * (self.drive_gain * left).tanh(),
+            self.level_gain * (self.drive_gain * right).tanh(),
+        )
+    }
+}
@@ -0,0 +1,39 @@
+// Slides the pitch of a voice from another key to its own key.
+#[derive(Debug, Default)]
+pub(crate) struct Portamento {
+    // The distance to the target pitch in keys.
+    offset: f32,
+    step: f32,
+}
+
+impl Portamento {
+    pub(crate) fn start(&mut self, offset: f32, time: f32) {
+        let sample_count = time * crate::SAMPLE_RATE as f32;
+        if offset != 0.0 && sample_count >= 1.0 {
+            self.offset = offset;
+            self.step = offset.abs() / sample_count;
+        } else {
+            self.stop();
+        }
+    }
+
+    pub(crate) fn stop(&mut self) {
+        self.offset = 0.0;
+        self.step = 0.0;
+    }
+
+    // The current offset of the pitch in keys.
+    pub(crate) fn get_offset(&self) -> f32 {
+        self.offset
+    }
+
+    pub(crate) fn render(&mut self) -> f32 {
+        let value = self.offset;
+        if self.offset > 0.0 {
+            self.offset = (self.offset - self.step).max(0.0);
+        } else if self.offset < 0.0 {
+            self.offset = (self.offset + self.step).min(0.0);
+        }
+        value
+    }
+}
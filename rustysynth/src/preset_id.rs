@@ -0,0 +1,38 @@
+/// Identifies a preset by the combination of the bank number and the program number.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct PresetId {
+    pub bank: u16,
+    pub program: u8,
+}
+
+impl PresetId {
+    /// The bank number of the GM percussion presets.
+    pub const PERCUSSION_BANK: u16 = 128;
+
+    pub const fn new(bank: u16, program: u8) -> Self {
+        Self { bank, program }
+    }
+
+    /// Returns `true` if the preset is in a percussion bank.
+    pub const fn is_percussion(&self) -> bool {
+        self.bank >= PresetId::PERCUSSION_BANK
+    }
+
+    /// Gets the preset of the GM sound set which substitutes this one.
+    /// Normally, the same program in the bank 0 will work.
+    /// For drums, it seems to be better to select the standard set (128:0).
+    pub const fn get_gm_fallback(&self) -> Self {
+        if self.is_percussion() {
+            PresetId::new(PresetId::PERCUSSION_BANK, 0)
+        } else {
+            PresetId::new(0, self.program)
+        }
+    }
+}
+
+/// Describes a preset provided by a sound source.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PresetDescriptor {
+    pub id: PresetId,
+    pub name: String,
+}
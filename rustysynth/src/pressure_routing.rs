@@ -0,0 +1,29 @@
+/// Specifies how the channel pressure and the polyphonic key pressure modulate the voices.
+/// Each value is the amount at the full pressure.
+/// If both pressures are given, the higher one is used.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PressureRouting {
+    /// The depth of the vibrato in cents, which is added to the one set by the modulation wheel.
+    pub vibrato_depth: f32,
+    /// The change of the filter cutoff in cents.
+    pub cutoff: f32,
+    /// The change of the volume in decibels.
+    pub volume: f32,
+}
+
+impl PressureRouting {
+    pub fn new(vibrato_depth: f32, cutoff: f32, volume: f32) -> Self {
+        Self {
+            vibrato_depth,
+            cutoff,
+            volume,
+        }
+    }
+}
+
+impl Default for PressureRouting {
+    /// The pressure deepens the vibrato in the same way as the modulation wheel.
+    fn default() -> Self {
+        Self::new(50.0, 0.0, 0.0)
+    }
+}
@@ -0,0 +1,210 @@
+use std::f64::consts;
+
+const SAMPLE_RATE: f64 = crate::SAMPLE_RATE as f64;
+
+/// The drum sounds rendered by `ProceduralSource`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Drum {
+    Kick,
+    Snare,
+    Clap,
+    SideStick,
+    ClosedHiHat,
+    OpenHiHat,
+    Crash,
+    Ride,
+    Tom,
+    Cowbell,
+    Shaker,
+    WoodBlock,
+    Triangle,
+}
+
+impl Drum {
+    pub(crate) const ALL: [Drum; 13] = [
+        Drum::Kick,
+        Drum::Snare,
+        Drum::Clap,
+        Drum::SideStick,
+        Drum::ClosedHiHat,
+        Drum::OpenHiHat,
+        Drum::Crash,
+        Drum::Ride,
+        Drum::Tom,
+        Drum::Cowbell,
+        Drum::Shaker,
+        Drum::WoodBlock,
+        Drum::Triangle,
+    ];
+
+    /// Gets the drum and the pitch shift in semitones for the key of the GM percussion map.
+    pub(crate) fn from_key(key: i32) -> Option<(Drum, i32)> {
+        let drum = match key {
+            35 => (Drum::Kick, -2),
+            36 => (Drum::Kick, 0),
+            37 => (Drum::SideStick, 0),
+            38 => (Drum::Snare, 0),
+            39 => (Drum::Clap, 0),
+            40 => (Drum::Snare, 3),
+            41 => (Drum::Tom, -8),
+            42 => (Drum::ClosedHiHat, 0),
+            43 => (Drum::Tom, -5),
+            44 => (Drum::ClosedHiHat, -3),
+            45 => (Drum::Tom, -2),
+            46 => (Drum::OpenHiHat, 0),
+            47 => (Drum::Tom, 0),
+            48 => (Drum::Tom, 3),
+            49 => (Drum::Crash, 0),
+            50 => (Drum::Tom, 5),
+            51 => (Drum::Ride, 0),
+            52 => (Drum::Crash, -3),
+            53 => (Drum::Cowbell, 10),
+            54 => (Drum::Shaker, 5),
+            55 => (Drum::Crash, 4),
+            56 => (Drum::Cowbell, 0),
+            57 => (Drum::Crash, 2),
+            58 => (Drum::Shaker, -5),
+            59 => (Drum::Ride, 2),
+            60 => (Drum::Tom, 14),
+            61 => (Drum::Tom, 10),
+            62 => (Drum::Tom, 9),
+            63 => (Drum::Tom, 8),
+            64 => (Drum::Tom, 5),
+            65 => (Drum::Tom, 12),
+            66 => (Drum::Tom, 7),
+            67 => (Drum::Cowbell, 5),
+            68 => (Drum::Cowbell, -2),
+            69 => (Drum::Shaker, 3),
+            70 => (Drum::Shaker, 7),
+            73 => (Drum::Shaker, -2),
+            74 => (Drum::Shaker, -4),
+            75 => (Drum::WoodBlock, 3),
+            76 => (Drum::WoodBlock, 0),
+            77 => (Drum::WoodBlock, -4),
+            80 | 81 => (Drum::Triangle, 0),
+            _ => return None,
+        };
+        Some(drum)
+    }
+
+    /// Renders the drum at the sample rate of the synthesizer.
+    /// The output is normalized so that the peak is 0.9.
+    pub(crate) fn render(self) -> Vec<f64> {
+        let mut noise = NoiseGenerator::new(self as u32 + 1);
+        let mut output = match self {
+            Drum::Kick => render(0.5, |t| {
+                // The pitch falls quickly from 150 Hz to 50 Hz.
+                let phase = 50.0 * t + 100.0 * 0.04 * (1.0 - (-t / 0.04).exp());
+                (2.0 * consts::PI * phase).sin() * decay(t, 0.15)
+            }),
+            Drum::Snare => render(0.35, |t| {
+                let tone = (2.0 * consts::PI * 180.0 * t).sin() * decay(t, 0.05);
+                tone + 0.8 * noise.bright() * decay(t, 0.07)
+            }),
+            Drum::Clap => render(0.3, |t| {
+                // A few short bursts followed by a tail.
+                let bursts = [0.0, 0.011, 0.022]
+                    .iter()
+                    .filter(|&&start| t >= start)
+                    .map(|start| decay(t - start, 0.004))
+                    .sum::<f64>();
+                noise.bright() * (bursts + 0.6 * delayed_decay(t, 0.03, 0.06))
+            }),
+            Drum::SideStick => render(0.1, |t| {
+                let tone = (2.0 * consts::PI * 1700.0 * t).sin() * decay(t, 0.008);
+                tone + 0.5 * noise.bright() * decay(t, 0.005)
+            }),
+            Drum::ClosedHiHat => render(0.15, |t| noise.bright() * decay(t, 0.02)),
+            Drum::OpenHiHat => render(0.7, |t| noise.bright() * decay(t, 0.15)),
+            Drum::Crash => render(2.0, |t| noise.bright() * decay(t, 0.45)),
+            Drum::Ride => render(1.5, |t| {
+                let ring = metallic(t, &[3100.0, 4700.0, 5900.0]);
+                (0.4 * noise.bright() + 0.3 * ring) * decay(t, 0.35)
+            }),
+            Drum::Tom => render(0.6, |t| {
+                // The pitch falls a little from 130 Hz to 110 Hz.
+                let phase = 110.0 * t + 20.0 * 0.05 * (1.0 - (-t / 0.05).exp());
+                let tone = (2.0 * consts::PI * phase).sin() * decay(t, 0.12);
+                tone + 0.15 * noise.white() * decay(t, 0.01)
+            }),
+            Drum::Cowbell => render(0.4, |t| metallic(t, &[540.0, 800.0]) * decay(t, 0.07)),
+            Drum::Shaker => render(0.15, |t| {
+                let envelope = (t / 0.01).min(1.0) * delayed_decay(t, 0.01, 0.025);
+                noise.bright() * envelope
+            }),
+            Drum::WoodBlock => render(0.12, |t| {
+                let tone = (2.0 * consts::PI * 1200.0 * t).sin()
+                    + 0.3 * (2.0 * consts::PI * 3300.0 * t).sin();
+                tone * decay(t, 0.015)
+            }),
+            Drum::Triangle => render(1.5, |t| {
+                metallic(t, &[2500.0, 4200.0, 6900.0]) * decay(t, 0.4)
+            }),
+        };
+
+        let peak = output.iter().fold(0.0_f64, |peak, &x| peak.max(x.abs()));
+        if peak > 0.0 {
+            output.iter_mut().for_each(|x| *x *= 0.9 / peak);
+        }
+        output
+    }
+}
+
+fn render<F: FnMut(f64) -> f64>(length: f64, mut f: F) -> Vec<f64> {
+    let sample_count = (SAMPLE_RATE * length) as usize;
+    (0..sample_count)
+        .map(|i| f(i as f64 / SAMPLE_RATE))
+        .collect()
+}
+
+fn decay(t: f64, time_constant: f64) -> f64 {
+    (-t / time_constant).exp()
+}
+
+fn delayed_decay(t: f64, delay: f64, time_constant: f64) -> f64 {
+    if t < delay {
+        1.0
+    } else {
+        decay(t - delay, time_constant)
+    }
+}
+
+// The sum of inharmonic partials, which sounds like a small bell.
+fn metallic(t: f64, frequencies: &[f64]) -> f64 {
+    frequencies
+        .iter()
+        .map(|frequency| (2.0 * consts::PI * frequency * t).sin())
+        .sum::<f64>()
+        / frequencies.len() as f64
+}
+
+/// A deterministic white noise generator (xorshift32).
+pub(crate) struct NoiseGenerator {
+    state: u32,
+    previous: f64,
+}
+
+impl NoiseGenerator {
+    pub(crate) fn new(seed: u32) -> Self {
+        Self {
+            state: seed.wrapping_mul(0x9E37_79B9) | 1,
+            previous: 0.0,
+        }
+    }
+
+    /// Gets the next value in the range of -1 to 1.
+    pub(crate) fn white(&mut self) -> f64 {
+        self.state ^= self.state << 13;
+        self.state ^= self.state >> 17;
+        self.state ^= self.state << 5;
+        self.state as f64 / u32::MAX as f64 * 2.0 - 1.0
+    }
+
+    /// Gets the next value with the low frequencies removed, which suits cymbals.
+    pub(crate) fn bright(&mut self) -> f64 {
+        let value = self.white();
+        let output = 0.5 * (value - self.previous);
+        self.previous = value;
+        output
+    }
+}
@@ -0,0 +1,662 @@
+use crate::oscillator::View;
+use crate::preset_id::{PresetDescriptor, PresetId};
+use crate::procedural_drums::{Drum, NoiseGenerator};
+use crate::signal_generator::{GeneratorSlot, SignalGenerator};
+use crate::soundfont_math::key_to_hertz;
+use crate::synthesizer::{Sound, SoundSource};
+use crate::{FilterType, LoopMode};
+use anyhow::{Result, anyhow};
+use std::f64::consts;
+use std::sync::Arc;
+
+// The length of one cycle of the periodic waveforms.
+const TABLE_LENGTH: usize = 2048;
+// The periodic waveforms are played at 440 Hz by the key 69.
+const TABLE_ROOT_KEY: i32 = 69;
+const TABLE_SAMPLE_RATE: i32 = 440 * TABLE_LENGTH as i32;
+
+// The band-limited tables are prepared per octave.
+// Each table is used up to the key, and has no harmonics above 18 kHz for the key + 2 semitones.
+const TABLE_TOP_KEYS: [i32; 9] = [24, 36, 48, 60, 72, 84, 96, 108, 127];
+const MAX_HARMONIC_FREQUENCY: f64 = 18000.0;
+
+// The RMS level of the periodic waveforms, where 1 is the full scale.
+const TABLE_RMS: f64 = 0.4;
+
+const NOISE_LENGTH: usize = 32768;
+
+// The SoundFont default for the unused envelope and LFO times (-12000 timecents).
+const MINIMUM_TIME: f32 = 0.001;
+
+// The SoundFont default for the LFO frequencies (0 cents).
+const DEFAULT_LFO_FREQUENCY: f32 = 8.176;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Waveform {
+    Sine,
+    Triangle,
+    Saw,
+    Square,
+    // A pulse wave with the duty cycle of 25%.
+    Pulse,
+    Noise,
+}
+
+impl Waveform {
+    const ALL: [Waveform; 6] = [
+        Waveform::Sine,
+        Waveform::Triangle,
+        Waveform::Saw,
+        Waveform::Square,
+        Waveform::Pulse,
+        Waveform::Noise,
+    ];
+}
+
+/// The parameters of a GM program played by `ProceduralSource`.
+#[derive(Debug, Clone, Copy)]
+struct Patch {
+    waveform: Waveform,
+    attack: f32,
+    decay: f32,
+    // The attenuation of the sustain level in decibels.
+    sustain: f32,
+    release: f32,
+    cutoff: f32,
+    filter_type: FilterType,
+}
+
+// The sound fades out while the key is held, like a piano or a plucked string.
+const fn percussive(waveform: Waveform, decay: f32, cutoff: f32) -> Patch {
+    Patch {
+        waveform,
+        attack: 0.002,
+        decay,
+        sustain: 100.0,
+        release: 0.3,
+        cutoff,
+        filter_type: FilterType::StateVariableLowPass,
+    }
+}
+
+// The sound continues while the key is held, like an organ or a wind instrument.
+const fn sustained(waveform: Waveform, attack: f32, release: f32, cutoff: f32) -> Patch {
+    Patch {
+        waveform,
+        attack,
+        decay: 1.0,
+        sustain: 0.0,
+        release,
+        cutoff,
+        filter_type: FilterType::StateVariableLowPass,
+    }
+}
+
+// The sound is shaped by another type of filter, whose cutoff is the one of the patch.
+const fn filtered(patch: Patch, filter_type: FilterType) -> Patch {
+    Patch {
+        filter_type,
+        ..patch
+    }
+}
+
+use Waveform::*;
+
+#[rustfmt::skip]
+const PROGRAMS: [(&str, Patch); 128] = [
+    // Piano
+    ("Acoustic Grand Piano", percussive(Saw, 3.0, 3000.0)),
+    ("Bright Acoustic Piano", percussive(Saw, 3.0, 6000.0)),
+    ("Electric Grand Piano", percussive(Saw, 2.5, 2500.0)),
+    ("Honky-tonk Piano", percussive(Pulse, 2.5, 4000.0)),
+    ("Electric Piano 1", percussive(Sine, 3.0, 20000.0)),
+    ("Electric Piano 2", percussive(Triangle, 2.5, 20000.0)),
+    ("Harpsichord", percussive(Pulse, 1.5, 6000.0)),
+    ("Clavi", percussive(Pulse, 1.0, 4000.0)),
+    // Chromatic Percussion
+    ("Celesta", percussive(Sine, 1.5, 20000.0)),
+    ("Glockenspiel", percussive(Sine, 1.2, 20000.0)),
+    ("Music Box", percussive(Sine, 1.0, 20000.0)),
+    ("Vibraphone", percussive(Sine, 2.5, 20000.0)),
+    ("Marimba", percussive(Sine, 0.5, 20000.0)),
+    ("Xylophone", percussive(Sine, 0.4, 20000.0)),
+    ("Tubular Bells", percussive(Triangle, 3.0, 20000.0)),
+    ("Dulcimer", percussive(Saw, 1.5, 3000.0)),
+    // Organ
+    ("Drawbar Organ", sustained(Square, 0.01, 0.1, 3000.0)),
+    ("Percussive Organ", sustained(Pulse, 0.005, 0.1, 4000.0)),
+    ("Rock Organ", sustained(Saw, 0.01, 0.1, 3000.0)),
+    ("Church Organ", sustained(Square, 0.05, 0.3, 2500.0)),
+    ("Reed Organ", sustained(Pulse, 0.05, 0.2, 2000.0)),
+    ("Accordion", sustained(Saw, 0.03, 0.15, 3000.0)),
+    ("Harmonica", sustained(Pulse, 0.03, 0.15, 2500.0)),
+    ("Tango Accordion", sustained(Pulse, 0.03, 0.15, 3000.0)),
+    // Guitar
+    ("Acoustic Guitar (nylon)", percussive(Triangle, 1.5, 3000.0)),
+    ("Acoustic Guitar (steel)", percussive(Saw, 1.8, 4000.0)),
+    ("Electric Guitar (jazz)", percussive(Saw, 1.5, 1500.0)),
+    ("Electric Guitar (clean)", percussive(Pulse, 2.0, 3000.0)),
+    ("Electric Guitar (muted)", percussive(Saw, 0.3, 1500.0)),
+    ("Overdriven Guitar", sustained(Square, 0.005, 0.15, 3000.0)),
+    ("Distortion Guitar", sustained(Saw, 0.005, 0.15, 4000.0)),
+    ("Guitar Harmonics", percussive(Sine, 1.5, 20000.0)),
+    // Bass
+    ("Acoustic Bass", percussive(Triangle, 1.5, 1500.0)),
+    ("Electric Bass (finger)", percussive(Saw, 1.5, 1000.0)),
+    ("Electric Bass (pick)", percussive(Saw, 1.2, 2000.0)),
+    ("Fretless Bass", sustained(Triangle, 0.02, 0.15, 1500.0)),
+    ("Slap Bass 1", percussive(Pulse, 0.8, 3000.0)),
+    ("Slap Bass 2", percussive(Pulse, 0.8, 4000.0)),
+    ("Synth Bass 1", percussive(Saw, 1.0, 1200.0)),
+    ("Synth Bass 2", percussive(Square, 1.0, 1500.0)),
+    // Strings
+    ("Violin", sustained(Saw, 0.08, 0.3, 4000.0)),
+    ("Viola", sustained(Saw, 0.08, 0.3, 3000.0)),
+    ("Cello", sustained(Saw, 0.1, 0.3, 2000.0)),
+    ("Contrabass", sustained(Saw, 0.1, 0.3, 1200.0)),
+    ("Tremolo Strings", sustained(Saw, 0.1, 0.4, 3000.0)),
+    ("Pizzicato Strings", percussive(Saw, 0.4, 2000.0)),
+    ("Orchestral Harp", percussive(Triangle, 2.0, 20000.0)),
+    ("Timpani", percussive(Sine, 1.5, 20000.0)),
+    // Ensemble
+    ("String Ensemble 1", sustained(Saw, 0.15, 0.5, 3000.0)),
+    ("String Ensemble 2", sustained(Saw, 0.3, 0.6, 2500.0)),
+    ("Synth Strings 1", sustained(Saw, 0.2, 0.6, 4000.0)),
+    ("Synth Strings 2", sustained(Saw, 0.3, 0.6, 3000.0)),
+    ("Choir Aahs", sustained(Triangle, 0.15, 0.5, 2000.0)),
+    ("Voice Oohs", sustained(Sine, 0.15, 0.5, 20000.0)),
+    ("Synth Voice", sustained(Triangle, 0.1, 0.4, 3000.0)),
+    ("Orchestra Hit", percussive(Saw, 0.5, 4000.0)),
+    // Brass
+    ("Trumpet", sustained(Saw, 0.03, 0.15, 4000.0)),
+    ("Trombone", sustained(Saw, 0.04, 0.15, 2500.0)),
+    ("Tuba", sustained(Saw, 0.05, 0.15, 1200.0)),
+    ("Muted Trumpet", sustained(Pulse, 0.03, 0.15, 3000.0)),
+    ("French Horn", sustained(Saw, 0.06, 0.2, 1500.0)),
+    ("Brass Section", sustained(Saw, 0.05, 0.2, 3500.0)),
+    ("Synth Brass 1", sustained(Saw, 0.02, 0.2, 5000.0)),
+    ("Synth Brass 2", sustained(Saw, 0.05, 0.3, 3000.0)),
+    // Reed
+    ("Soprano Sax", sustained(Pulse, 0.03, 0.15, 4000.0)),
+    ("Alto Sax", sustained(Pulse, 0.03, 0.15, 3000.0)),
+    ("Tenor Sax", sustained(Saw, 0.03, 0.15, 2500.0)),
+    ("Baritone Sax", sustained(Saw, 0.03, 0.15, 1500.0)),
+    ("Oboe", sustained(Pulse, 0.03, 0.15, 3000.0)),
+    ("English Horn", sustained(Pulse, 0.04, 0.15, 2000.0)),
+    ("Bassoon", sustained(Square, 0.04, 0.15, 1500.0)),
+    ("Clarinet", sustained(Square, 0.03, 0.15, 2500.0)),
+    // Pipe
+    ("Piccolo", sustained(Sine, 0.03, 0.1, 20000.0)),
+    ("Flute", sustained(Triangle, 0.05, 0.15, 3000.0)),
+    ("Recorder", sustained(Triangle, 0.03, 0.1, 2500.0)),
+    ("Pan Flute", sustained(Sine, 0.06, 0.2, 20000.0)),
+    ("Blown Bottle", sustained(Sine, 0.08, 0.2, 20000.0)),
+    ("Shakuhachi", sustained(Triangle, 0.08, 0.2, 2000.0)),
+    ("Whistle", sustained(Sine, 0.03, 0.1, 20000.0)),
+    ("Ocarina", sustained(Sine, 0.04, 0.1, 20000.0)),
+    // Synth Lead
+    ("Lead 1 (square)", sustained(Square, 0.005, 0.1, 6000.0)),
+    ("Lead 2 (sawtooth)", sustained(Saw, 0.005, 0.1, 6000.0)),
+    ("Lead 3 (calliope)", sustained(Triangle, 0.02, 0.15, 4000.0)),
+    ("Lead 4 (chiff)", sustained(Pulse, 0.005, 0.15, 3000.0)),
+    ("Lead 5 (charang)", sustained(Saw, 0.005, 0.15, 3500.0)),
+    ("Lead 6 (voice)", sustained(Triangle, 0.05, 0.2, 2500.0)),
+    ("Lead 7 (fifths)", sustained(Saw, 0.01, 0.15, 4000.0)),
+    ("Lead 8 (bass + lead)", sustained(Saw, 0.005, 0.15, 2500.0)),
+    // Synth Pad
+    ("Pad 1 (new age)", sustained(Triangle, 0.4, 1.0, 4000.0)),
+    ("Pad 2 (warm)", filtered(sustained(Saw, 0.5, 1.0, 1500.0), FilterType::LowPass24)),
+    ("Pad 3 (polysynth)", sustained(Saw, 0.2, 0.8, 3000.0)),
+    ("Pad 4 (choir)", sustained(Triangle, 0.5, 1.0, 2000.0)),
+    ("Pad 5 (bowed)", sustained(Saw, 0.6, 1.0, 2000.0)),
+    ("Pad 6 (metallic)", sustained(Pulse, 0.4, 1.0, 3000.0)),
+    ("Pad 7 (halo)", sustained(Triangle, 0.6, 1.2, 3000.0)),
+    ("Pad 8 (sweep)", sustained(Saw, 0.8, 1.2, 2000.0)),
+    // Synth Effects
+    ("FX 1 (rain)", percussive(Sine, 2.0, 20000.0)),
+    ("FX 2 (soundtrack)", sustained(Saw, 0.6, 1.2, 2000.0)),
+    ("FX 3 (crystal)", percussive(Sine, 2.5, 20000.0)),
+    ("FX 4 (atmosphere)", percussive(Triangle, 3.0, 3000.0)),
+    ("FX 5 (brightness)", sustained(Saw, 0.4, 1.2, 6000.0)),
+    ("FX 6 (goblins)", sustained(Pulse, 0.8, 1.0, 1500.0)),
+    ("FX 7 (echoes)", sustained(Triangle, 0.3, 1.5, 3000.0)),
+    ("FX 8 (sci-fi)", filtered(sustained(Square, 0.4, 1.2, 2500.0), FilterType::Notch)),
+    // Ethnic
+    ("Sitar", percussive(Saw, 2.0, 5000.0)),
+    ("Banjo", percussive(Pulse, 0.8, 5000.0)),
+    ("Shamisen", percussive(Saw, 0.8, 4000.0)),
+    ("Koto", percussive(Triangle, 1.2, 4000.0)),
+    ("Kalimba", percussive(Sine, 0.6, 20000.0)),
+    ("Bag pipe", sustained(Saw, 0.05, 0.1, 3000.0)),
+    ("Fiddle", sustained(Saw, 0.05, 0.2, 4000.0)),
+    ("Shanai", sustained(Pulse, 0.04, 0.15, 3500.0)),
+    // Percussive
+    ("Tinkle Bell", percussive(Sine, 1.0, 20000.0)),
+    ("Agogo", percussive(Triangle, 0.4, 20000.0)),
+    ("Steel Drums", percussive(Triangle, 0.8, 20000.0)),
+    ("Woodblock", percussive(Sine, 0.15, 20000.0)),
+    ("Taiko Drum", percussive(Sine, 0.5, 20000.0)),
+    ("Melodic Tom", percussive(Sine, 0.5, 20000.0)),
+    ("Synth Drum", percussive(Triangle, 0.4, 20000.0)),
+    ("Reverse Cymbal", filtered(sustained(Noise, 1.5, 0.1, 5000.0), FilterType::HighPass)),
+    // Sound Effects
+    ("Guitar Fret Noise", percussive(Noise, 0.1, 3000.0)),
+    ("Breath Noise", filtered(sustained(Noise, 0.05, 0.1, 1500.0), FilterType::BandPass)),
+    ("Seashore", sustained(Noise, 1.0, 1.5, 1500.0)),
+    ("Bird Tweet", sustained(Sine, 0.02, 0.1, 20000.0)),
+    ("Telephone Ring", sustained(Square, 0.005, 0.05, 4000.0)),
+    ("Helicopter", filtered(sustained(Noise, 0.3, 0.5, 800.0), FilterType::BandPass)),
+    ("Applause", sustained(Noise, 0.5, 1.0, 6000.0)),
+    ("Gunshot", percussive(Noise, 0.4, 4000.0)),
+];
+
+const DRUM_KIT_NAME: &str = "Standard";
+
+// The drums have no envelope of their own, and the release is long enough not to cut them.
+const DRUM_RELEASE: f32 = 2.0;
+
+/// A part of the wave data, played as a sample.
+#[derive(Debug, Clone, Copy)]
+struct Table {
+    start: usize,
+    end: usize,
+    sample_rate: i32,
+    loop_mode: LoopMode,
+}
+
+/// A sound source which generates simple waveforms without any file.
+/// It provides the 128 programs of the GM sound set in the bank 0,
+/// and a basic drum kit in the bank 128.
+/// The sounds are rough, but they are good enough to audition MIDI files.
+#[derive(Debug)]
+pub struct ProceduralSource {
+    wave_data: Arc<[i16]>,
+    // The band-limited tables of each waveform, indexed by the position in TABLE_TOP_KEYS.
+    waveforms: Vec<Vec<Table>>,
+    // Indexed by the position in Drum::ALL.
+    drums: Vec<Table>,
+}
+
+impl ProceduralSource {
+    /// Creates a new source by generating all of its waveforms.
+    pub fn new() -> Self {
+        let mut wave_data: Vec<i16> = Vec::new();
+
+        let waveforms = Waveform::ALL
+            .iter()
+            .map(|&waveform| match waveform {
+                Waveform::Noise => {
+                    let mut noise = NoiseGenerator::new(1);
+                    let samples: Vec<f64> = (0..NOISE_LENGTH).map(|_| noise.white()).collect();
+                    vec![add_table(
+                        &mut wave_data,
+                        &samples,
+                        crate::SAMPLE_RATE,
+                        LoopMode::Continuous,
+                    )]
+                }
+                // A sine wave is rendered by SineGenerator, which needs no table.
+                Waveform::Sine => Vec::new(),
+                _ => TABLE_TOP_KEYS
+                    .iter()
+                    .map(|&top_key| {
+                        let highest = key_to_hertz((top_key + 2) as f32) as f64;
+                        let harmonics = ((MAX_HARMONIC_FREQUENCY / highest) as usize)
+                            .clamp(1, TABLE_LENGTH / 2 - 1);
+                        add_periodic_table(&mut wave_data, waveform, harmonics)
+                    })
+                    .collect(),
+            })
+            .collect();
+
+        let drums = Drum::ALL
+            .iter()
+            .map(|drum| {
+                add_table(
+                    &mut wave_data,
+                    &drum.render(),
+                    crate::SAMPLE_RATE,
+                    LoopMode::NoLoop,
+                )
+            })
+            .collect();
+
+        Self {
+            wave_data: Arc::from(wave_data),
+            waveforms,
+            drums,
+        }
+    }
+
+    fn get_view(&self, table: &Table) -> View<i16> {
+        View {
+            data: self.wave_data.clone(),
+            start: table.start,
+            end: table.end,
+        }
+    }
+}
+
+impl Default for ProceduralSource {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SoundSource for ProceduralSource {
+    #[allow(refining_impl_trait)]
+    fn get_sound(
+        &mut self,
+        preset_id: PresetId,
+        key: i32,
+        _velocity: i32,
+    ) -> Result<ProceduralSound> {
+        if preset_id.is_percussion() {
+            let (drum, shift) =
+                Drum::from_key(key).ok_or_else(|| anyhow!("No drums found for key: {}", key))?;
+            let index = Drum::ALL.iter().position(|&d| d == drum).unwrap_or(0);
+            let table = &self.drums[index];
+            return Ok(ProceduralSound {
+                wave_data: self.get_view(table),
+                sample_rate: table.sample_rate,
+                loop_mode: table.loop_mode,
+                root_key: key - shift,
+                patch: Patch {
+                    waveform: Waveform::Noise,
+                    attack: MINIMUM_TIME,
+                    decay: MINIMUM_TIME,
+                    sustain: 0.0,
+                    release: DRUM_RELEASE,
+                    cutoff: 20000.0,
+                    filter_type: FilterType::StateVariableLowPass,
+                },
+            });
+        }
+
+        let (_, patch) = PROGRAMS
+            .get(preset_id.program as usize)
+            .copied()
+            .ok_or_else(|| anyhow!("No programs found for patch_id: {}", preset_id.program))?;
+        if patch.waveform == Waveform::Sine {
+            return Ok(ProceduralSound {
+                wave_data: View {
+                    data: self.wave_data.clone(),
+                    start: 0,
+                    end: 0,
+                },
+                sample_rate: crate::SAMPLE_RATE,
+                loop_mode: LoopMode::NoLoop,
+                root_key: TABLE_ROOT_KEY,
+                patch,
+            });
+        }
+
+        let levels = &self.waveforms[patch.waveform as usize];
+        let level = TABLE_TOP_KEYS
+            .iter()
+            .position(|&top_key| key <= top_key)
+            .unwrap_or(TABLE_TOP_KEYS.len() - 1)
+            .min(levels.len() - 1);
+        let table = &levels[level];
+        let root_key = if patch.waveform == Waveform::Noise {
+            60
+        } else {
+            TABLE_ROOT_KEY
+        };
+
+        Ok(ProceduralSound {
+            wave_data: self.get_view(table),
+            sample_rate: table.sample_rate,
+            loop_mode: table.loop_mode,
+            root_key,
+            patch,
+        })
+    }
+
+    fn get_presets(&self) -> Vec<PresetDescriptor> {
+        let mut presets: Vec<PresetDescriptor> = PROGRAMS
+            .iter()
+            .enumerate()
+            .map(|(program, (name, _))| PresetDescriptor {
+                id: PresetId::new(0, program as u8),
+                name: name.to_string(),
+            })
+            .collect();
+        presets.push(PresetDescriptor {
+            id: PresetId::new(PresetId::PERCUSSION_BANK, 0),
+            name: DRUM_KIT_NAME.to_string(),
+        });
+        presets
+    }
+
+    fn resolve_preset(&self, preset_id: PresetId) -> Option<PresetId> {
+        Some(preset_id.get_gm_fallback())
+    }
+
+    fn contains_preset(&self, preset_id: PresetId) -> bool {
+        preset_id == PresetId::new(PresetId::PERCUSSION_BANK, 0)
+            || (preset_id.bank == 0 && (preset_id.program as usize) < PROGRAMS.len())
+    }
+}
+
+/// A sound generated by `ProceduralSource`.
+#[derive(Debug, Clone)]
+pub struct ProceduralSound {
+    wave_data: View<i16>,
+    sample_rate: i32,
+    loop_mode: LoopMode,
+    root_key: i32,
+    patch: Patch,
+}
+
+impl Sound for ProceduralSound {
+    fn get_wave_data(&self) -> View<i16> {
+        self.wave_data.clone()
+    }
+
+    fn sample_sample_rate(&self) -> i32 {
+        self.sample_rate
+    }
+
+    fn get_sample_start_loop(&self) -> i32 {
+        0
+    }
+
+    fn get_sample_end_loop(&self) -> i32 {
+        self.wave_data.len() as i32
+    }
+
+    fn get_initial_filter_cutoff_frequency(&self) -> f32 {
+        self.patch.cutoff
+    }
+
+    fn get_filter_type(&self) -> FilterType {
+        self.patch.filter_type
+    }
+
+    fn get_reverb_effects_send(&self) -> f32 {
+        0.0
+    }
+
+    fn get_delay_modulation_lfo(&self) -> f32 {
+        MINIMUM_TIME
+    }
+
+    fn get_frequency_modulation_lfo(&self) -> f32 {
+        DEFAULT_LFO_FREQUENCY
+    }
+
+    fn get_delay_vibrato_lfo(&self) -> f32 {
+        MINIMUM_TIME
+    }
+
+    fn get_frequency_vibrato_lfo(&self) -> f32 {
+        DEFAULT_LFO_FREQUENCY
+    }
+
+    fn get_delay_modulation_envelope(&self) -> f32 {
+        MINIMUM_TIME
+    }
+
+    fn get_attack_modulation_envelope(&self) -> f32 {
+        MINIMUM_TIME
+    }
+
+    fn get_hold_modulation_envelope(&self) -> f32 {
+        MINIMUM_TIME
+    }
+
+    fn get_decay_modulation_envelope(&self) -> f32 {
+        MINIMUM_TIME
+    }
+
+    fn get_release_modulation_envelope(&self) -> f32 {
+        MINIMUM_TIME
+    }
+
+    fn get_delay_volume_envelope(&self) -> f32 {
+        MINIMUM_TIME
+    }
+
+    fn get_attack_volume_envelope(&self) -> f32 {
+        self.patch.attack
+    }
+
+    fn get_hold_volume_envelope(&self) -> f32 {
+        MINIMUM_TIME
+    }
+
+    fn get_decay_volume_envelope(&self) -> f32 {
+        self.patch.decay
+    }
+
+    fn get_sustain_volume_envelope(&self) -> f32 {
+        self.patch.sustain
+    }
+
+    fn get_release_volume_envelope(&self) -> f32 {
+        self.patch.release
+    }
+
+    fn get_initial_attenuation(&self) -> f32 {
+        0.0
+    }
+
+    fn get_fine_tune(&self) -> i32 {
+        0
+    }
+
+    fn get_sample_modes(&self) -> LoopMode {
+        self.loop_mode
+    }
+
+    fn get_root_key(&self) -> i32 {
+        self.root_key
+    }
+
+    fn start_generator(&self, slot: &mut GeneratorSlot) {
+        if self.patch.waveform == Waveform::Sine {
+            slot.start(SineGenerator::new());
+        }
+    }
+}
+
+// Renders a sine wave as loud as the tables of the other waveforms.
+#[derive(Debug)]
+struct SineGenerator {
+    phase: f64,
+    // The phase increment is computed again only when the pitch changes.
+    last_pitch: f32,
+    increment: f64,
+}
+
+impl SineGenerator {
+    fn new() -> Self {
+        Self {
+            phase: 0.0,
+            last_pitch: f32::NAN,
+            increment: 0.0,
+        }
+    }
+}
+
+impl SignalGenerator for SineGenerator {
+    fn render(&mut self, pitch: f32) -> Option<f32> {
+        if pitch != self.last_pitch {
+            self.increment = key_to_hertz(pitch) as f64 / crate::SAMPLE_RATE as f64;
+            self.last_pitch = pitch;
+        }
+
+        let value = TABLE_RMS * consts::SQRT_2 * (2.0 * consts::PI * self.phase).sin();
+        self.phase = (self.phase + self.increment).fract();
+        Some(value as f32)
+    }
+}
+
+/// Generates one cycle of the waveform from its harmonics, normalized to the same loudness.
+fn add_periodic_table(wave_data: &mut Vec<i16>, waveform: Waveform, harmonics: usize) -> Table {
+    let sine: Vec<f64> = (0..TABLE_LENGTH)
+        .map(|i| (2.0 * consts::PI * i as f64 / TABLE_LENGTH as f64).sin())
+        .collect();
+    let cosine = |i: usize| sine[(i + TABLE_LENGTH / 4) % TABLE_LENGTH];
+
+    let mut samples = vec![0_f64; TABLE_LENGTH];
+    for k in 1..=harmonics {
+        // The Lanczos sigma factor reduces the ringing caused by the missing harmonics.
+        let x = consts::PI * k as f64 / (harmonics + 1) as f64;
+        let sigma = if k == 1 { 1.0 } else { x.sin() / x };
+        let kf = k as f64;
+
+        let (sine_gain, cosine_gain) = match waveform {
+            Waveform::Triangle if k % 2 == 1 => {
+                let sign = if k % 4 == 1 { 1.0 } else { -1.0 };
+                (sign / (kf * kf), 0.0)
+            }
+            Waveform::Saw => {
+                let sign = if k % 2 == 1 { 1.0 } else { -1.0 };
+                (sign / kf, 0.0)
+            }
+            Waveform::Square if k % 2 == 1 => (1.0 / kf, 0.0),
+            Waveform::Pulse => (0.0, (consts::PI * kf * 0.25).sin() / kf),
+            _ => (0.0, 0.0),
+        };
+
+        for (i, sample) in samples.iter_mut().enumerate() {
+            let phase = (k * i) % TABLE_LENGTH;
+            *sample += sigma * (sine_gain * sine[phase] + cosine_gain * cosine(phase));
+        }
+    }
+
+    add_table(wave_data, &samples, TABLE_SAMPLE_RATE, LoopMode::Continuous)
+}
+
+/// Appends the samples to the wave data.
+/// The periodic waveforms are normalized by their RMS so that they sound equally loud.
+fn add_table(
+    wave_data: &mut Vec<i16>,
+    samples: &[f64],
+    sample_rate: i32,
+    loop_mode: LoopMode,
+) -> Table {
+    let gain = if loop_mode == LoopMode::NoLoop {
+        1.0
+    } else {
+        let rms = (samples.iter().map(|x| x * x).sum::<f64>() / samples.len() as f64).sqrt();
+        if rms > 0.0 { TABLE_RMS / rms } else { 0.0 }
+    };
+
+    let start = wave_data.len();
+    wave_data.extend(
+        samples
+            .iter()
+            .map(|x| (32767.0 * gain * x).round().clamp(-32768.0, 32767.0) as i16),
+    );
+    let end = wave_data.len();
+
+    Table {
+        start,
+        end,
+        sample_rate,
+        loop_mode,
+    }
+}
@@ -0,0 +1,94 @@
+use std::any::Any;
+use std::fmt;
+
+/// Generates the waveform of a voice in place of a sample.
+/// The output goes through the envelopes, the filter and the panning of the voice,
+/// in the same way as the output of a sample.
+pub trait SignalGenerator: Send {
+    /// Renders the next output sample at the sample rate of the synthesizer.
+    /// Returns `None` when the sound has ended, which stops the voice.
+    ///
+    /// # Arguments
+    ///
+    /// * `pitch` - The pitch as a key number, including the pitch bend, the modulation and the fine tune.
+    fn render(&mut self, pitch: f32) -> Option<f32>;
+
+    /// Called when the note is released.
+    /// The volume envelope starts its release regardless of what is done here.
+    fn release(&mut self) {}
+}
+
+impl fmt::Debug for dyn SignalGenerator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("SignalGenerator")
+    }
+}
+
+/// The place of a voice where a sound puts its generator.
+/// The generator is kept after the voice ends,
+/// so a later note which starts a generator of the same type reuses the allocation.
+#[derive(Default)]
+pub struct GeneratorSlot {
+    generator: Option<Box<dyn SlotGenerator>>,
+    active: bool,
+}
+
+impl GeneratorSlot {
+    /// Starts the generator for the new voice in place of the sample.
+    /// If the slot holds a generator of the same type, it is overwritten without allocation.
+    ///
+    /// # Arguments
+    ///
+    /// * `generator` - The generator in its initial state.
+    pub fn start<G: SignalGenerator + 'static>(&mut self, generator: G) {
+        match self
+            .generator
+            .as_mut()
+            .and_then(|slot| slot.as_any_mut().downcast_mut::<G>())
+        {
+            Some(slot) => *slot = generator,
+            None => self.generator = Some(Box::new(generator)),
+        }
+        self.active = true;
+    }
+
+    // Makes the voice play the sample, unless a generator is started again.
+    pub(crate) fn clear(&mut self) {
+        self.active = false;
+    }
+
+    pub(crate) fn get_mut(&mut self) -> Option<&mut dyn SignalGenerator> {
+        match &mut self.generator {
+            Some(generator) if self.active => Some(generator.as_signal_generator_mut()),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn is_active(&self) -> bool {
+        self.active
+    }
+}
+
+impl fmt::Debug for GeneratorSlot {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("GeneratorSlot")
+            .field("active", &self.active)
+            .finish_non_exhaustive()
+    }
+}
+
+// Lets the slot find the type of the generator it holds.
+trait SlotGenerator: SignalGenerator {
+    fn as_any_mut(&mut self) -> &mut dyn Any;
+    fn as_signal_generator_mut(&mut self) -> &mut dyn SignalGenerator;
+}
+
+impl<G: SignalGenerator + 'static> SlotGenerator for G {
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+
+    fn as_signal_generator_mut(&mut self) -> &mut dyn SignalGenerator {
+        self
+    }
+}
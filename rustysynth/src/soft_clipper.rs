@@ -0,0 +1,60 @@
+use crate::effect::Effect;
+use crate::soundfont_math::decibels_to_linear;
+
+/// Saturates the signal smoothly so that it never exceeds the full scale.
+/// The signal below the threshold passes through unchanged.
+#[derive(Debug)]
+pub struct SoftClipper {
+    threshold: f32,
+
+    knee_start: f32,
+}
+
+impl SoftClipper {
+    /// Creates a new soft clipper.
+    ///
+    /// # Arguments
+    ///
+    /// * `threshold` - The level where the saturation starts in decibels below the full scale.
+    pub fn new(threshold: f32) -> Self {
+        let mut clipper = Self {
+            threshold: 0.0,
+            knee_start: 1.0,
+        };
+        clipper.set_threshold(threshold);
+        clipper
+    }
+
+    /// Gets the level where the saturation starts in decibels.
+    pub fn get_threshold(&self) -> f32 {
+        self.threshold
+    }
+
+    /// Sets the level where the saturation starts in decibels.
+    /// Values above 0 dB are treated as 0 dB, which makes the clipper hard.
+    pub fn set_threshold(&mut self, value: f32) {
+        self.threshold = value.min(0.0);
+        self.knee_start = decibels_to_linear(self.threshold);
+    }
+
+    fn clip(&self, x: f32) -> f32 {
+        let magnitude = x.abs();
+        if magnitude <= self.knee_start {
+            return x;
+        }
+
+        let range = 1.0 - self.knee_start;
+        let clipped = if range > 0.0 {
+            self.knee_start + range * ((magnitude - self.knee_start) / range).tanh()
+        } else {
+            1.0
+        };
+        clipped.copysign(x)
+    }
+}
+
+impl Effect for SoftClipper {
+    fn process(&mut self, left: f32, right: f32) -> (f32, f32) {
+        (self.clip(left), self.clip(right))
+    }
+}
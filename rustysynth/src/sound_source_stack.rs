@@ -0,0 +1,216 @@
+use crate::preset_id::{PresetDescriptor, PresetId};
+use crate::synthesizer::{Sound, SoundSource};
+use anyhow::{Result, anyhow};
+use std::collections::BTreeMap;
+
+/// A sound source in a `SoundSourceStack`.
+#[derive(Debug)]
+pub struct SoundSourceLayer<S> {
+    pub(crate) source: S,
+    pub(crate) bank_offset: u16,
+}
+
+impl<S> SoundSourceLayer<S> {
+    /// Gets the sound source of the layer.
+    pub fn get_source(&self) -> &S {
+        &self.source
+    }
+
+    /// Gets the sound source of the layer for modification.
+    pub fn get_source_mut(&mut self) -> &mut S {
+        &mut self.source
+    }
+
+    /// Gets the bank offset of the layer.
+    /// The bank N of the source is exposed as the bank N + offset of the stack.
+    pub fn get_bank_offset(&self) -> u16 {
+        self.bank_offset
+    }
+
+    fn map_preset_id(&self, preset_id: PresetId) -> Option<PresetId> {
+        let bank = preset_id.bank.checked_sub(self.bank_offset)?;
+        Some(PresetId::new(bank, preset_id.program))
+    }
+
+    // The ID given to the base layer when no layer has the preset.
+    // The banks below the offset are not owned by the layer, so its bank 0 is used instead.
+    fn map_default_preset_id(&self, preset_id: PresetId) -> PresetId {
+        self.map_preset_id(preset_id)
+            .unwrap_or(PresetId::new(0, preset_id.program))
+    }
+
+    fn unmap_preset_id(&self, preset_id: PresetId) -> Option<PresetId> {
+        let bank = preset_id.bank.checked_add(self.bank_offset)?;
+        Some(PresetId::new(bank, preset_id.program))
+    }
+}
+
+/// A sound source which combines an ordered stack of sound sources.
+/// The first layer has the highest priority.
+/// A preset is looked up in every layer before falling back to the GM sound set,
+/// and only the last layer is asked for its own default preset.
+/// The layers are searched with `SoundSource::contains_preset`,
+/// so a source which claims every preset, as the default implementation does,
+/// hides all the layers below it.
+#[derive(Debug)]
+pub struct SoundSourceStack<S> {
+    layers: Vec<SoundSourceLayer<S>>,
+}
+
+impl<S> Default for SoundSourceStack<S> {
+    fn default() -> Self {
+        Self { layers: Vec::new() }
+    }
+}
+
+impl<S> SoundSourceStack<S> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a source with the lowest priority.
+    ///
+    /// # Arguments
+    ///
+    /// * `source` - The sound source to add.
+    /// * `bank_offset` - The offset added to the bank numbers of the source.
+    pub fn push(&mut self, source: S, bank_offset: u16) {
+        self.layers.push(SoundSourceLayer {
+            source,
+            bank_offset,
+        });
+    }
+
+    /// Inserts a source at the given priority, where 0 is the highest.
+    ///
+    /// # Arguments
+    ///
+    /// * `index` - The position of the new layer.
+    /// * `source` - The sound source to add.
+    /// * `bank_offset` - The offset added to the bank numbers of the source.
+    pub fn insert(&mut self, index: usize, source: S, bank_offset: u16) {
+        self.layers.insert(
+            index,
+            SoundSourceLayer {
+                source,
+                bank_offset,
+            },
+        );
+    }
+
+    /// Removes the layer at the given position and returns its source.
+    pub fn remove(&mut self, index: usize) -> S {
+        self.layers.remove(index).source
+    }
+
+    /// Moves the layer at `from` to the position `to`, shifting the layers in between.
+    pub fn move_layer(&mut self, from: usize, to: usize) {
+        let layer = self.layers.remove(from);
+        self.layers.insert(to, layer);
+    }
+
+    /// Changes the bank offset of the layer at the given position.
+    pub fn set_bank_offset(&mut self, index: usize, bank_offset: u16) {
+        self.layers[index].bank_offset = bank_offset;
+    }
+
+    /// Gets the layers ordered from the highest priority to the lowest.
+    pub fn get_layers(&self) -> &[SoundSourceLayer<S>] {
+        &self.layers[..]
+    }
+
+    /// Gets the layers for modification.
+    pub fn get_layers_mut(&mut self) -> &mut [SoundSourceLayer<S>] {
+        &mut self.layers[..]
+    }
+
+    pub fn len(&self) -> usize {
+        self.layers.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.layers.is_empty()
+    }
+}
+
+impl<S: SoundSource> SoundSourceStack<S> {
+    fn find_layer(&self, preset_id: PresetId) -> Option<(usize, PresetId)> {
+        self.layers.iter().enumerate().find_map(|(i, layer)| {
+            layer
+                .map_preset_id(preset_id)
+                .filter(|id| layer.source.contains_preset(*id))
+                .map(|id| (i, id))
+        })
+    }
+
+    fn find_layer_with_fallback(&self, preset_id: PresetId) -> Option<(usize, PresetId)> {
+        // Try fallback to the GM sound set only after every layer was searched.
+        self.find_layer(preset_id)
+            .or_else(|| self.find_layer(preset_id.get_gm_fallback()))
+    }
+}
+
+impl<S> From<S> for SoundSourceStack<S> {
+    fn from(source: S) -> Self {
+        let mut stack = Self::new();
+        stack.push(source, 0);
+        stack
+    }
+}
+
+impl<S> From<Vec<S>> for SoundSourceStack<S> {
+    fn from(sources: Vec<S>) -> Self {
+        let mut stack = Self::new();
+        for source in sources {
+            stack.push(source, 0);
+        }
+        stack
+    }
+}
+
+impl<S: SoundSource> SoundSource for SoundSourceStack<S> {
+    fn get_sound(&mut self, preset_id: PresetId, key: i32, velocity: i32) -> Result<impl Sound> {
+        match self.find_layer_with_fallback(preset_id) {
+            Some((i, id)) => self.layers[i].source.get_sound(id, key, velocity),
+            None => match self.layers.last_mut() {
+                // Let the base layer choose its default preset.
+                Some(layer) => {
+                    let id = layer.map_default_preset_id(preset_id);
+                    layer.source.get_sound(id, key, velocity)
+                }
+                None => Err(anyhow!("The sound source stack is empty")),
+            },
+        }
+    }
+
+    fn get_presets(&self) -> Vec<PresetDescriptor> {
+        // The presets of the higher priority layers hide the ones with the same ID.
+        let mut presets: BTreeMap<PresetId, PresetDescriptor> = BTreeMap::new();
+        for layer in &self.layers {
+            for mut preset in layer.source.get_presets() {
+                if let Some(id) = layer.unmap_preset_id(preset.id) {
+                    preset.id = id;
+                    presets.entry(id).or_insert(preset);
+                }
+            }
+        }
+        presets.into_values().collect()
+    }
+
+    fn resolve_preset(&self, preset_id: PresetId) -> Option<PresetId> {
+        match self.find_layer_with_fallback(preset_id) {
+            Some((i, id)) => self.layers[i].unmap_preset_id(id),
+            None => {
+                let layer = self.layers.last()?;
+                let id = layer
+                    .source
+                    .resolve_preset(layer.map_default_preset_id(preset_id))?;
+                layer.unmap_preset_id(id)
+            }
+        }
+    }
+
+    fn contains_preset(&self, preset_id: PresetId) -> bool {
+        self.find_layer(preset_id).is_some()
+    }
+}
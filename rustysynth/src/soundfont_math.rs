@@ -24,6 +24,11 @@ pub fn linear_to_decibels(x: f32) -> f32 {
     20.0 * x.log10()
 }
 
+/// Converts a key number to the frequency in Hz, where the key 69 is 440 Hz.
+pub fn key_to_hertz(x: f32) -> f32 {
+    440.0 * 2_f32.powf((1.0 / 12.0) * (x - 69.0))
+}
+
 pub fn key_number_to_multiplying_factor(cents: i32, key: i32) -> f32 {
     timecents_to_seconds((cents * (60 - key)) as f32)
 }
@@ -0,0 +1,110 @@
+use std::f32::consts;
+
+use crate::FilterType;
+use crate::bi_quad_filter::BiQuadFilter;
+
+// The Q values of the two stages which make a 4th order Butterworth response.
+const LOW_PASS_24_Q1: f32 = 0.541_196_1;
+const LOW_PASS_24_Q2: f32 = 1.306_563;
+
+// The cutoff is limited below the Nyquist frequency, where the prewarping diverges.
+const MAX_CUTOFF_RATIO: f32 = 0.49;
+
+/// A state variable filter discretized by the topology-preserving transform.
+/// Unlike the biquad, the state does not depend on the coefficients,
+/// so the cutoff can be changed at every sample without instability or zipper noise.
+#[derive(Debug, Default)]
+pub(crate) struct StateVariableFilter {
+    filter_type: FilterType,
+    stages: [SvfStage; 2],
+}
+
+#[derive(Debug, Default)]
+struct SvfStage {
+    g: f32,
+    k: f32,
+
+    ic1eq: f32,
+    ic2eq: f32,
+}
+
+impl StateVariableFilter {
+    pub(crate) fn clear_buffer(&mut self) {
+        for stage in &mut self.stages {
+            stage.ic1eq = 0.0;
+            stage.ic2eq = 0.0;
+        }
+    }
+
+    pub(crate) fn set_filter(
+        &mut self,
+        filter_type: FilterType,
+        cutoff_frequency: f32,
+        resonance: f32,
+    ) {
+        self.filter_type = filter_type;
+
+        let sample_rate = crate::SAMPLE_RATE as f32;
+        let cutoff_frequency = cutoff_frequency.clamp(1.0, MAX_CUTOFF_RATIO * sample_rate);
+        let g = (consts::PI * cutoff_frequency / sample_rate).tan();
+
+        let q = BiQuadFilter::resonance_to_q(resonance).max(0.01);
+        if filter_type == FilterType::LowPass24 {
+            // The resonance is given to the second stage, which has the sharper peak.
+            let scale = q * consts::SQRT_2;
+            self.stages[0].set(g, LOW_PASS_24_Q1);
+            self.stages[1].set(g, LOW_PASS_24_Q2 * scale);
+        } else {
+            self.stages[0].set(g, q);
+        }
+    }
+
+    pub(crate) fn render(&mut self, input: f32) -> f32 {
+        match self.filter_type {
+            FilterType::LowPass | FilterType::StateVariableLowPass => {
+                self.stages[0].render(input).low
+            }
+            FilterType::HighPass => self.stages[0].render(input).high,
+            FilterType::BandPass => self.stages[0].render(input).band,
+            FilterType::Notch => {
+                let output = self.stages[0].render(input);
+                output.low + output.high
+            }
+            FilterType::LowPass24 => {
+                let output = self.stages[0].render(input).low;
+                self.stages[1].render(output).low
+            }
+        }
+    }
+}
+
+struct SvfOutput {
+    low: f32,
+    band: f32,
+    high: f32,
+}
+
+impl SvfStage {
+    fn set(&mut self, g: f32, q: f32) {
+        self.g = g;
+        self.k = 1.0 / q;
+    }
+
+    fn render(&mut self, input: f32) -> SvfOutput {
+        let a1 = 1.0 / (1.0 + self.g * (self.g + self.k));
+        let a2 = self.g * a1;
+        let a3 = self.g * a2;
+
+        let v3 = input - self.ic2eq;
+        let v1 = a1 * self.ic1eq + a2 * v3;
+        let v2 = self.ic2eq + a2 * self.ic1eq + a3 * v3;
+        self.ic1eq = 2.0 * v1 - self.ic1eq;
+        self.ic2eq = 2.0 * v2 - self.ic2eq;
+
+        SvfOutput {
+            low: v2,
+            band: v1,
+            high: input - self.k * v1 - v2,
+        }
+    }
+}
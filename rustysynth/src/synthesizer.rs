@@ -1,16 +1,32 @@
-use crate::LoopMode;
+use crate::{BankSelectMode, FilterType, LoopMode};
 use crate::channel::Channel;
+use crate::effect::EffectChain;
 use crate::oscillator::View;
+use crate::preset_id::{PresetDescriptor, PresetId};
+use crate::pressure_routing::PressureRouting;
 use crate::reverb::Reverb;
-use crate::soundfont_math::NON_AUDIBLE;
-use crate::voice::Voice;
+use crate::signal_generator::GeneratorSlot;
+use crate::soundfont_math::{NON_AUDIBLE, decibels_to_linear, linear_to_decibels};
+use crate::voice::{Voice, get_pan_gains};
+use crate::wave_data::WaveView;
 use anyhow::Result;
+use std::sync::Arc;
 
 pub trait Sound {
-    fn sample_sample_rate(&self) -> i32;
-    fn get_sample_start_loop(&self) -> i32;
-    fn get_sample_end_loop(&self) -> i32;
+    fn sample_sample_rate(&self) -> i32 {
+        crate::SAMPLE_RATE
+    }
+    fn get_sample_start_loop(&self) -> i32 {
+        0
+    }
+    fn get_sample_end_loop(&self) -> i32 {
+        0
+    }
     fn get_initial_filter_cutoff_frequency(&self) -> f32;
+    /// Gets the type of the filter, whose cutoff frequency is given by `get_initial_filter_cutoff_frequency`.
+    fn get_filter_type(&self) -> FilterType {
+        FilterType::LowPass
+    }
     fn get_reverb_effects_send(&self) -> f32;
     fn get_delay_modulation_lfo(&self) -> f32;
     fn get_frequency_modulation_lfo(&self) -> f32;
@@ -29,24 +45,103 @@ pub trait Sound {
     fn get_release_volume_envelope(&self) -> f32;
     fn get_initial_attenuation(&self) -> f32;
     fn get_fine_tune(&self) -> i32;
-    fn get_sample_modes(&self) -> LoopMode;
-    fn get_root_key(&self) -> i32;
-    fn get_wave_data(&self) -> View<i16>;
+    fn get_sample_modes(&self) -> LoopMode {
+        LoopMode::NoLoop
+    }
+    fn get_root_key(&self) -> i32 {
+        60
+    }
+    fn get_wave_data(&self) -> View<i16> {
+        View {
+            data: Arc::from([]),
+            start: 0,
+            end: 0,
+        }
+    }
+
+    /// Gets the sample played by the voice.
+    /// Sounds whose samples are kept as bytes override this, so that the samples are read in place.
+    /// The default implementation uses `get_wave_data`.
+    fn get_wave_view(&self) -> WaveView {
+        self.get_wave_data().into()
+    }
+
+    /// Starts the generator which renders the waveform of a new voice, by `GeneratorSlot::start`.
+    /// If no generator is started, the voice plays the sample given by `get_wave_view`
+    /// with the loop and the root key of the sound.
+    /// Sounds which provide a generator need not implement the sample related methods.
+    ///
+    /// # Arguments
+    ///
+    /// * `slot` - The slot of the voice, which may hold the generator of a previous note.
+    fn start_generator(&self, _slot: &mut GeneratorSlot) {}
 }
 
 pub trait SoundSource {
-    fn get_sound(&mut self, id: u16, key: i32, velocity: i32) -> Result<impl Sound>;
+    fn get_sound(&mut self, id: PresetId, key: i32, velocity: i32) -> Result<impl Sound>;
+
+    /// Gets the presets provided by the source, ordered by their IDs.
+    /// Sources which cannot enumerate their presets return an empty list.
+    fn get_presets(&self) -> Vec<PresetDescriptor> {
+        Vec::new()
+    }
+
+    /// Finds the preset with the given name.
+    fn find_preset(&self, name: &str) -> Option<PresetId> {
+        self.get_presets()
+            .into_iter()
+            .find(|preset| preset.name == name)
+            .map(|preset| preset.id)
+    }
+
+    /// Gets the preset actually used for the given ID after any fallback.
+    /// Returns `None` if the source has no preset to play.
+    fn resolve_preset(&self, id: PresetId) -> Option<PresetId> {
+        Some(id)
+    }
+
+    /// Checks if the source has a preset for the given ID without any fallback.
+    /// `SoundSourceStack` relies on this to find the layer which plays the preset,
+    /// so it is called for every note and should not allocate.
+    /// The default implementation claims every preset, like `resolve_preset`.
+    fn contains_preset(&self, _id: PresetId) -> bool {
+        true
+    }
 }
 
 #[derive(Debug)]
-pub struct Synthesizer<Source, const CHANNELS: usize = 8, const VOICES: usize = 16> {
+pub struct Synthesizer<Source, const CHANNELS: usize = 16, const VOICES: usize = 16> {
     pub(crate) sound_font: Source,
     channels: [Channel; CHANNELS],
     voices: [Voice; VOICES],
+    channel_effects: [EffectChain; CHANNELS],
+    master_effects: EffectChain,
+    // The master volume actually applied, which follows the target smoothly.
     master_volume: f32,
+    // The master volume set by the application, which is kept by reset.
+    user_master_volume: f32,
+    // The master volume and balance set by MIDI messages, which are cleared by reset.
+    midi_master_volume: f32,
+    midi_master_balance: f32,
+    master_balance: f32,
+    soft_pedal_attenuation: f32,
+    soft_pedal_cutoff_change: f32,
+    bank_select_mode: BankSelectMode,
     reverb: Reverb,
 }
 
+const DEFAULT_MASTER_VOLUME: f32 = 0.5;
+
+// The channel 10 of GM, which plays drums by default.
+// A synthesizer with fewer channels has no drum part by default.
+const PERCUSSION_CHANNEL: usize = 9;
+
+const DEFAULT_SOFT_PEDAL_ATTENUATION: f32 = 4.0;
+const DEFAULT_SOFT_PEDAL_CUTOFF_CHANGE: f32 = 600.0;
+
+// The master volume and balance reach 63% of a new value in 10 ms.
+const MASTER_SMOOTHING: f32 = 1.0 / (0.01 * crate::SAMPLE_RATE as f32);
+
 macro_rules! set_channel {
     ($synth_fun:ident) => {
         set_channel!($synth_fun, u8);
@@ -65,16 +160,139 @@ impl<Source: SoundSource, const CHANNELS: usize, const VOICES: usize>
     where
         Source: From<S>,
     {
-        Self {
+        let mut synthesizer = Self {
             sound_font: sound_font_pre.into(),
             channels: core::array::from_fn(|_| Channel::default()),
             voices: core::array::from_fn(|_| Voice::default()),
-            master_volume: 0.5,
+            channel_effects: core::array::from_fn(|_| EffectChain::default()),
+            master_effects: EffectChain::default(),
+            master_volume: DEFAULT_MASTER_VOLUME,
+            user_master_volume: DEFAULT_MASTER_VOLUME,
+            midi_master_volume: 1.0,
+            midi_master_balance: 0.0,
+            master_balance: 0.0,
+            soft_pedal_attenuation: DEFAULT_SOFT_PEDAL_ATTENUATION,
+            soft_pedal_cutoff_change: DEFAULT_SOFT_PEDAL_CUTOFF_CHANGE,
+            bank_select_mode: BankSelectMode::default(),
             reverb: Reverb::default(),
+        };
+        synthesizer.reset();
+        synthesizer
+    }
+
+    /// Gets the sound source used by the synthesizer.
+    pub fn get_sound_source(&self) -> &Source {
+        &self.sound_font
+    }
+
+    /// Gets the sound source used by the synthesizer for modification.
+    pub fn get_sound_source_mut(&mut self) -> &mut Source {
+        &mut self.sound_font
+    }
+
+    /// Gets the number of the channels, which must be above any channel given to the methods.
+    pub fn get_channel_count(&self) -> usize {
+        CHANNELS
+    }
+
+    /// Gets the insert effects of the channel.
+    pub fn get_channel_effects(&self, channel: u8) -> &EffectChain {
+        &self.channel_effects[channel as usize]
+    }
+
+    /// Gets the insert effects of the channel for modification.
+    /// The effects process the mono sum of the voices of the channel,
+    /// and their output is panned by the channel pan before it is mixed with the other channels.
+    /// The pan of each voice, such as the pan of a stereo sample, is not applied to the sum.
+    pub fn get_channel_effects_mut(&mut self, channel: u8) -> &mut EffectChain {
+        &mut self.channel_effects[channel as usize]
+    }
+
+    /// Gets the master volume as a linear gain.
+    pub fn get_master_volume(&self) -> f32 {
+        self.user_master_volume
+    }
+
+    /// Sets the master volume as a linear gain. The default value is 0.5.
+    /// The change is smoothed to avoid zipper noise.
+    /// The master volume is kept by `reset`, and is combined with the one set by MIDI messages.
+    pub fn set_master_volume(&mut self, value: f32) {
+        self.user_master_volume = value.max(0.0);
+    }
+
+    /// Gets the master volume in decibels.
+    pub fn get_master_volume_decibels(&self) -> f32 {
+        linear_to_decibels(self.user_master_volume)
+    }
+
+    /// Sets the master volume in decibels, where 0 dB is the full scale.
+    pub fn set_master_volume_decibels(&mut self, value: f32) {
+        self.set_master_volume(decibels_to_linear(value));
+    }
+
+    /// Sets the master volume from the 14-bit value of the GM Master Volume SysEx.
+    /// The value is cleared by `reset`.
+    pub fn set_midi_master_volume(&mut self, value: u16) {
+        self.midi_master_volume = value.min(16383) as f32 / 16383.0;
+    }
+
+    /// Sets the master balance from the 14-bit value of the GM Master Balance SysEx.
+    /// The value 8192 is the center. The value is cleared by `reset`.
+    pub fn set_midi_master_balance(&mut self, value: u16) {
+        self.midi_master_balance = (value.min(16383) as f32 - 8192.0) / 8192.0;
+    }
+
+    /// Gets how the bank select messages choose the bank.
+    pub fn get_bank_select_mode(&self) -> BankSelectMode {
+        self.bank_select_mode
+    }
+
+    /// Sets how the bank select messages choose the bank.
+    /// The default value is `BankSelectMode::Gs`. The mode is kept by `reset`, and takes effect from the next note.
+    pub fn set_bank_select_mode(&mut self, value: BankSelectMode) {
+        self.bank_select_mode = value;
+        for channel in &mut self.channels {
+            channel.set_bank_select_mode(value);
         }
     }
 
+    /// Gets the attenuation applied by the soft pedal in decibels.
+    pub fn get_soft_pedal_attenuation(&self) -> f32 {
+        self.soft_pedal_attenuation
+    }
+
+    /// Sets the attenuation applied by the soft pedal in decibels. The default value is 4 dB.
+    /// The soft pedal affects the notes played while it is down.
+    pub fn set_soft_pedal_attenuation(&mut self, value: f32) {
+        self.soft_pedal_attenuation = value.max(0.0);
+    }
+
+    /// Gets how much the soft pedal lowers the filter cutoff in cents.
+    pub fn get_soft_pedal_cutoff_change(&self) -> f32 {
+        self.soft_pedal_cutoff_change
+    }
+
+    /// Sets how much the soft pedal lowers the filter cutoff in cents,
+    /// which makes the sound darker. The default value is 600 cents.
+    pub fn set_soft_pedal_cutoff_change(&mut self, value: f32) {
+        self.soft_pedal_cutoff_change = value.max(0.0);
+    }
+
+    /// Gets the effects of the master bus.
+    pub fn get_master_effects(&self) -> &EffectChain {
+        &self.master_effects
+    }
+
+    /// Gets the effects of the master bus for modification.
+    /// The effects process the final output after the master volume.
+    /// The chain is empty by default. A typical chain is an `Equalizer`,
+    /// a `SoftClipper` and a `Limiter` in this order, which keeps dense music from clipping.
+    pub fn get_master_effects_mut(&mut self) -> &mut EffectChain {
+        &mut self.master_effects
+    }
+
     set_channel!(set_bank);
+    set_channel!(set_bank_lsb);
     set_channel!(set_modulation_coarse);
     set_channel!(set_modulation_fine);
     set_channel!(data_entry_coarse);
@@ -86,6 +304,16 @@ impl<Source: SoundSource, const CHANNELS: usize, const VOICES: usize>
     set_channel!(set_expression_coarse);
     set_channel!(set_expression_fine);
     set_channel!(set_hold_pedal);
+    set_channel!(set_soft_pedal);
+    set_channel!(set_portamento);
+    set_channel!(set_portamento_time_coarse);
+    set_channel!(set_portamento_time_fine);
+    set_channel!(set_portamento_control);
+    set_channel!(set_harmonic_content);
+    set_channel!(set_release_time);
+    set_channel!(set_attack_time);
+    set_channel!(set_brightness);
+    set_channel!(set_decay_time);
     set_channel!(set_reverb_send);
     set_channel!(set_chorus_send);
     set_channel!(set_nrpn_coarse);
@@ -94,10 +322,78 @@ impl<Source: SoundSource, const CHANNELS: usize, const VOICES: usize>
     set_channel!(set_rpn_fine);
     set_channel!(set_patch);
     set_channel!(set_pitch_bend, u16);
+    set_channel!(set_channel_pressure);
+
+    /// Sets the pressure on a key, which affects only the notes of the key.
+    ///
+    /// # Arguments
+    ///
+    /// * `channel` - The channel of the key.
+    /// * `key` - The key to which the pressure is applied.
+    /// * `value` - The pressure from 0 to 127.
+    pub fn set_poly_pressure(&mut self, channel: u8, key: u8, value: u8) {
+        self.channels[channel as usize].set_poly_pressure(key, value);
+    }
+
+    /// Gets how the pressure modulates the voices of the channel.
+    pub fn get_pressure_routing(&self, channel: u8) -> &PressureRouting {
+        self.channels[channel as usize].get_pressure_routing()
+    }
+
+    /// Sets how the pressure modulates the voices of the channel.
+    /// The routing is kept by `reset`.
+    pub fn set_pressure_routing(&mut self, channel: u8, value: PressureRouting) {
+        self.channels[channel as usize].set_pressure_routing(value);
+    }
+
+    /// Returns `true` if the channel is a drum part.
+    /// In `BankSelectMode::Xg`, a channel which selects the drum kits or the SFX kits is also a drum part.
+    pub fn is_percussion(&self, channel: u8) -> bool {
+        self.channels[channel as usize].is_percussion()
+    }
+
+    /// Makes the channel a drum part or a normal part.
+    /// On a drum part, the program change selects a drum kit in the percussion bank,
+    /// the pitch bend and the tuning are ignored,
+    /// and the notes without loop play to the end regardless of the note-off.
+    /// Only the channel 10 is a drum part after `reset`,
+    /// so a synthesizer with fewer than 10 channels has no drum part unless it is set here.
+    pub fn set_percussion(&mut self, channel: u8, percussion: bool) {
+        self.channels[channel as usize].set_percussion(percussion);
+    }
+
+    /// Sets the sostenuto pedal, which sustains only the notes held when the pedal goes down.
+    pub fn set_sostenuto_pedal(&mut self, channel: u8, value: u8) {
+        let channel_info = &mut self.channels[channel as usize];
+        let was_down = channel_info.get_sostenuto_pedal();
+        channel_info.set_sostenuto_pedal(value);
+        if channel_info.get_sostenuto_pedal() && !was_down {
+            for voice in &mut self.voices {
+                if voice.channel == channel as i32 {
+                    voice.catch_sostenuto();
+                }
+            }
+        }
+    }
 
     pub fn note_off(&mut self, channel: i32, key: i32) {
+        let channel_info = &mut self.channels[channel as usize];
+        if channel_info.is_mono() {
+            // The note held before the released one sounds again.
+            if let Some(fallback) = channel_info.release_key(key as u8) {
+                let portamento_source = channel_info.take_portamento_source(fallback);
+                let portamento_time = portamento_source.map(|_| channel_info.get_portamento_time());
+                if let Some(voice) = self.voices.iter_mut().find(|voice| {
+                    voice.channel == channel && voice.key == key && voice.is_playing()
+                }) {
+                    voice.change_key(fallback as i32, portamento_time);
+                    return;
+                }
+            }
+        }
+
         for voice in &mut self.voices {
-            if voice.channel == channel && voice.key == key {
+            if voice.channel == channel && voice.key == key && !voice.is_one_shot() {
                 voice.end();
             }
         }
@@ -109,14 +405,48 @@ impl<Source: SoundSource, const CHANNELS: usize, const VOICES: usize>
             return;
         }
 
+        let channel_info = &mut self.channels[channel as usize];
+        // The aftertouch of the previous note on the key does not apply to the new note.
+        channel_info.set_poly_pressure(key as u8, 0);
+        let portamento_source = channel_info.take_portamento_source(key as u8);
+
+        // In mono mode, a note played while another sounds takes over its voice.
+        // The sounding note may be one which was released but is sustained by the pedals.
+        if channel_info.is_mono() {
+            channel_info.press_key(key as u8);
+            let portamento_time = portamento_source.map(|_| channel_info.get_portamento_time());
+            if let Some(voice) = self
+                .voices
+                .iter_mut()
+                .find(|voice| voice.channel == channel && voice.is_sounding())
+            {
+                voice.change_key(key, portamento_time);
+                return;
+            }
+        }
+
         let voice_idx = self.allocate_voice();
         let channel_info = &self.channels[channel as usize];
 
-        if let Ok(region_pair) =
-            self.sound_font
-                .get_sound(channel_info.get_preset_id(), key, velocity)
-        {
-            self.voices[voice_idx].start(&region_pair, channel, key, velocity)
+        // The variation falls back to its capital tone only if the source itself
+        // would not play the variation as it is.
+        let preset_id = channel_info.get_preset_id();
+        let preset_id = match channel_info.get_variation_fallback() {
+            Some(fallback) if self.sound_font.resolve_preset(preset_id) != Some(preset_id) => {
+                fallback
+            }
+            _ => preset_id,
+        };
+
+        if let Ok(region_pair) = self.sound_font.get_sound(preset_id, key, velocity) {
+            let voice = &mut self.voices[voice_idx];
+            voice.start(&region_pair, channel_info, channel, key, velocity);
+            if let Some(source) = portamento_source {
+                voice.start_portamento(source as i32, channel_info.get_portamento_time());
+            }
+            if channel_info.get_soft_pedal() {
+                voice.soften(self.soft_pedal_attenuation, self.soft_pedal_cutoff_change);
+            }
         }
     }
 
@@ -148,6 +478,11 @@ impl<Source: SoundSource, const CHANNELS: usize, const VOICES: usize>
     }
 
     fn note_off_all_(&mut self, channel: Option<i32>, immediate: bool) {
+        for (ch, channel_info) in self.channels.iter_mut().enumerate() {
+            if channel.is_none_or(|channel| channel as usize == ch) {
+                channel_info.release_all_keys();
+            }
+        }
         for voice in &mut self.voices {
             let select = match channel {
                 Some(ch) => voice.channel == ch,
@@ -171,6 +506,21 @@ impl<Source: SoundSource, const CHANNELS: usize, const VOICES: usize>
         self.note_off_all_(Some(channel), immediate);
     }
 
+    /// Switches the channel between mono mode and poly mode.
+    /// In mono mode, the channel plays one note at a time.
+    /// A note played while another is held changes the pitch of the sounding note
+    /// without restarting it, and the held note sounds again when the new note is released.
+    /// Switching the mode stops the notes on the channel.
+    ///
+    /// # Arguments
+    ///
+    /// * `channel` - The channel to switch.
+    /// * `mono` - `true` for mono mode, `false` for poly mode.
+    pub fn set_mono_mode(&mut self, channel: u8, mono: bool) {
+        self.note_off_all_channel(channel.into(), false);
+        self.channels[channel as usize].set_mono(mono);
+    }
+
     pub fn reset_all_controllers(&mut self) {
         for channel in &mut self.channels {
             channel.reset_all_controllers();
@@ -182,11 +532,20 @@ impl<Source: SoundSource, const CHANNELS: usize, const VOICES: usize>
     }
 
     pub fn reset(&mut self) {
-        self.master_volume = 0.5;
+        self.midi_master_volume = 1.0;
+        self.midi_master_balance = 0.0;
+        // All the notes stop, so the change need not be smoothed.
+        self.master_volume = self.user_master_volume;
+        self.master_balance = 0.0;
         self.note_off_all_(None, true);
-        for channel in &mut self.channels {
+        for (ch, channel) in self.channels.iter_mut().enumerate() {
+            channel.set_percussion(ch == PERCUSSION_CHANNEL);
             channel.reset();
         }
+        for effects in &mut self.channel_effects {
+            effects.reset();
+        }
+        self.master_effects.reset();
         self.reverb.reset();
     }
 
@@ -207,31 +566,59 @@ impl<Source: SoundSource, const CHANNELS: usize, const VOICES: usize>
             }
         }
 
+        fn smooth(current: f32, target: f32) -> f32 {
+            if (target - current).abs() < 1.0E-6 {
+                target
+            } else {
+                current + MASTER_SMOOTHING * (target - current)
+            }
+        }
+
+        self.master_volume = smooth(
+            self.master_volume,
+            self.user_master_volume * self.midi_master_volume,
+        );
+        self.master_balance = smooth(self.master_balance, self.midi_master_balance);
+
         let mut left = 0.0;
         let mut right = 0.0;
         let mut reverb_input = 0.0;
         // XXX chorus
 
+        // The channels with insert effects are summed before panning,
+        // and are panned and scaled by the master volume after the effects.
+        let mut channel_input = [0_f32; CHANNELS];
+
         for voice in &mut self.voices {
-            let channel_info = &self.channels[voice.channel as usize];
+            let channel = voice.channel as usize;
+            let channel_info = &self.channels[channel];
             let vo = voice.render(channel_info);
             if vo.is_none() {
                 continue;
             }
             let voice_out = vo.unwrap();
             // Normal output
-            write(
-                self.master_volume * voice.previous_mix_gain_left,
-                self.master_volume * voice.current_mix_gain_left,
-                voice_out,
-                &mut left,
-            );
-            write(
-                self.master_volume * voice.previous_mix_gain_right,
-                self.master_volume * voice.current_mix_gain_right,
-                voice_out,
-                &mut right,
-            );
+            if self.channel_effects[channel].is_active() {
+                write(
+                    voice.previous_mix_gain,
+                    voice.current_mix_gain,
+                    voice_out,
+                    &mut channel_input[channel],
+                );
+            } else {
+                write(
+                    self.master_volume * voice.previous_mix_gain_left,
+                    self.master_volume * voice.current_mix_gain_left,
+                    voice_out,
+                    &mut left,
+                );
+                write(
+                    self.master_volume * voice.previous_mix_gain_right,
+                    self.master_volume * voice.current_mix_gain_right,
+                    voice_out,
+                    &mut right,
+                );
+            }
 
             // Chorus
             /*
@@ -261,6 +648,17 @@ impl<Source: SoundSource, const CHANNELS: usize, const VOICES: usize>
             );
         }
 
+        for (channel, effects) in self.channel_effects.iter_mut().enumerate() {
+            // The effects keep running without input, so that tails such as delays are not cut.
+            if effects.is_active() {
+                let input = channel_input[channel];
+                let (effect_left, effect_right) = effects.process(input, input);
+                let (pan_left, pan_right) = get_pan_gains(self.channels[channel].get_pan());
+                multiply_add1(self.master_volume * pan_left, effect_left, &mut left);
+                multiply_add1(self.master_volume * pan_right, effect_right, &mut right);
+            }
+        }
+
         /* XXX
                 let (chorus_output_left, chorus_output_right) =
                     self.chorus.render(chorus_input_left, chorus_input_right);
@@ -275,6 +673,16 @@ impl<Source: SoundSource, const CHANNELS: usize, const VOICES: usize>
             multiply_add1(self.master_volume, reverb_output_right, &mut right);
         }
 
+        if self.master_balance != 0.0 {
+            left *= (1.0 - self.master_balance).min(1.0);
+            right *= (1.0 + self.master_balance).min(1.0);
+        }
+
+        // An empty or bypassed chain leaves the output bit-exact.
+        if self.master_effects.is_active() {
+            return self.master_effects.process(left, right);
+        }
+
         (left, right)
     }
 }
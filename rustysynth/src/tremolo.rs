@@ -0,0 +1,64 @@
+use std::f32::consts;
+
+use crate::effect::Effect;
+
+/// Modulates the volume periodically.
+#[derive(Debug)]
+pub struct Tremolo {
+    rate: f32,
+    depth: f32,
+
+    phase: f32,
+}
+
+impl Tremolo {
+    /// Creates a new tremolo.
+    ///
+    /// # Arguments
+    ///
+    /// * `rate` - The frequency of the modulation in Hz.
+    /// * `depth` - The amount of the modulation from 0 to 1. With 1, the volume goes down to zero.
+    pub fn new(rate: f32, depth: f32) -> Self {
+        Self {
+            rate,
+            depth: depth.clamp(0.0, 1.0),
+            phase: 0.0,
+        }
+    }
+
+    /// Gets the frequency of the modulation in Hz.
+    pub fn get_rate(&self) -> f32 {
+        self.rate
+    }
+
+    /// Sets the frequency of the modulation in Hz.
+    pub fn set_rate(&mut self, value: f32) {
+        self.rate = value;
+    }
+
+    /// Gets the amount of the modulation from 0 to 1.
+    pub fn get_depth(&self) -> f32 {
+        self.depth
+    }
+
+    /// Sets the amount of the modulation from 0 to 1.
+    pub fn set_depth(&mut self, value: f32) {
+        self.depth = value.clamp(0.0, 1.0);
+    }
+}
+
+impl Effect for Tremolo {
+    fn process(&mut self, left: f32, right: f32) -> (f32, f32) {
+        // The volume starts at the top, so that the attack of a note is not lost.
+        let gain = 1.0 - 0.5 * self.depth * (1.0 - (2.0 * consts::PI * self.phase).cos());
+
+        self.phase += self.rate / crate::SAMPLE_RATE as f32;
+        self.phase -= self.phase.floor();
+
+        (gain * left, gain * right)
+    }
+
+    fn reset(&mut self) {
+        self.phase = 0.0;
+    }
+}
@@ -1,9 +1,14 @@
+use crate::FilterType;
+use crate::LoopMode;
 use crate::bi_quad_filter::BiQuadFilter;
 use crate::channel::Channel;
 use crate::lfo::Lfo;
 use crate::modulation_envelope::ModulationEnvelope;
 use crate::oscillator::Oscillator;
+use crate::portamento::Portamento;
+use crate::signal_generator::GeneratorSlot;
 use crate::soundfont_math::*;
+use crate::state_variable_filter::StateVariableFilter;
 use crate::synthesizer::Sound;
 use crate::volume_envelope::VolumeEnvelope;
 use std::f32::consts;
@@ -24,8 +29,17 @@ pub(crate) struct Voice {
     vib_lfo: Lfo,
     mod_lfo: Lfo,
 
+    portamento: Portamento,
+
     oscillator: Oscillator,
+    // Replaces the oscillator if the sound provides its own waveform.
+    generator: GeneratorSlot,
+    // The fine tune in keys, which is applied by the oscillator for samples.
+    tune: f32,
     filter: BiQuadFilter,
+    // Used in place of the biquad for the filter types other than the SoundFont low-pass.
+    sv_filter: StateVariableFilter,
+    filter_type: FilterType,
 
     // A sudden change in the mix gain will cause pop noise.
     // To avoid this, we save the mix gain of the previous block,
@@ -35,6 +49,9 @@ pub(crate) struct Voice {
     pub(crate) previous_mix_gain_right: f32,
     pub(crate) current_mix_gain_left: f32,
     pub(crate) current_mix_gain_right: f32,
+    // The mix gain before panning, for the channels with insert effects.
+    pub(crate) previous_mix_gain: f32,
+    pub(crate) current_mix_gain: f32,
 
     pub(crate) previous_reverb_send: f32,
     pub(crate) previous_chorus_send: f32,
@@ -50,6 +67,11 @@ pub(crate) struct Voice {
     cutoff: f32,
     resonance: f32,
 
+    // The sound controllers and the pressure of the channel which were applied to the filter.
+    cutoff_change: f32,
+    harmonic_content: f32,
+    filter_changed: bool,
+
     vib_lfo_to_pitch: f32,
     mod_lfo_to_pitch: f32,
     mod_env_to_pitch: f32,
@@ -70,11 +92,24 @@ pub(crate) struct Voice {
     smoothed_cutoff: f32,
 
     voice_state: VoiceState,
+    // Set if the note plays to the end regardless of the note-off, like most drums.
+    one_shot: bool,
+    // Set if the note was held when the sostenuto pedal went down.
+    sostenuto: bool,
+    // Set when the envelope or the sample has come to the end.
+    ended: bool,
     pub(crate) voice_length: usize,
 }
 
 impl Voice {
-    pub(crate) fn start<S: Sound>(&mut self, region: &S, channel: i32, key: i32, velocity: i32) {
+    pub(crate) fn start<S: Sound>(
+        &mut self,
+        region: &S,
+        channel_info: &Channel,
+        channel: i32,
+        key: i32,
+        velocity: i32,
+    ) {
         self.channel = channel;
         self.key = key;
         self.velocity = velocity;
@@ -90,8 +125,10 @@ impl Voice {
         }
 
         self.cutoff = region.get_initial_filter_cutoff_frequency();
-        // XXX remove constant fields
-        self.resonance = 1.0;
+        self.cutoff_change = self.get_cutoff_change(channel_info);
+        self.harmonic_content = channel_info.get_harmonic_content();
+        self.resonance = Voice::get_resonance(self.harmonic_content);
+        self.filter_changed = false;
 
         self.vib_lfo_to_pitch = 0.0;
         self.mod_lfo_to_pitch = 0.0;
@@ -110,12 +147,13 @@ impl Voice {
 
         self.vol_env.start(
             region.get_delay_volume_envelope() as f64,
-            region.get_attack_volume_envelope() as f64,
+            (region.get_attack_volume_envelope() * channel_info.get_attack_time_factor()) as f64,
             region.get_hold_volume_envelope() as f64,
-            region.get_decay_volume_envelope() as f64,
+            (region.get_decay_volume_envelope() * channel_info.get_decay_time_factor()) as f64,
             decibels_to_linear(-region.get_sustain_volume_envelope()),
             // If the release time is shorter than 10 ms, it will be clamped to 10 ms to avoid pop noise.
-            region.get_release_volume_envelope().max(0.01) as f64,
+            (region.get_release_volume_envelope() * channel_info.get_release_time_factor())
+                .max(0.01) as f64,
         );
         self.mod_env.start(
             region.get_delay_modulation_envelope() as f64,
@@ -133,24 +171,91 @@ impl Voice {
             region.get_delay_modulation_lfo() as f64,
             region.get_frequency_modulation_lfo() as f64,
         );
-        self.oscillator.start(
-            region.get_wave_data(),
-            region.get_sample_modes(),
-            region.sample_sample_rate(),
-            region.get_sample_start_loop(),
-            region.get_sample_end_loop(),
-            region.get_root_key(),
-            region.get_fine_tune(),
-        );
-        self.filter.clear_buffer();
-        self.filter.set_low_pass_filter(self.cutoff, self.resonance);
+        self.portamento.stop();
+        self.generator.clear();
+        region.start_generator(&mut self.generator);
+        self.tune = 0.01 * region.get_fine_tune() as f32;
+        if !self.generator.is_active() {
+            self.oscillator.start(
+                region.get_wave_view(),
+                region.get_sample_modes(),
+                region.sample_sample_rate(),
+                region.get_sample_start_loop(),
+                region.get_sample_end_loop(),
+                region.get_root_key(),
+                region.get_fine_tune(),
+            );
+        }
+        // Looped drums, such as a roll, still need the note-off to stop.
+        self.one_shot = channel_info.is_percussion()
+            && !self.generator.is_active()
+            && region.get_sample_modes() == LoopMode::NoLoop;
+
+        let cutoff = cents_to_multiplying_factor(self.cutoff_change) * self.cutoff;
+        self.filter_type = region.get_filter_type();
+        if self.filter_type == FilterType::LowPass {
+            self.filter.clear_buffer();
+            self.filter.set_low_pass_filter(cutoff, self.resonance);
+        } else {
+            self.sv_filter.clear_buffer();
+            self.sv_filter
+                .set_filter(self.filter_type, cutoff, self.resonance);
+        }
 
-        self.smoothed_cutoff = self.cutoff;
+        self.smoothed_cutoff = cutoff;
 
         self.voice_state = VoiceState::Playing;
+        self.sostenuto = false;
+        self.ended = false;
         self.voice_length = 0;
     }
 
+    // Applies the soft pedal to a note which has just started.
+    pub(crate) fn soften(&mut self, attenuation: f32, cutoff_change: f32) {
+        self.note_gain *= decibels_to_linear(-attenuation);
+        let factor = cents_to_multiplying_factor(-cutoff_change);
+        self.cutoff *= factor;
+        self.smoothed_cutoff *= factor;
+        self.filter_changed = true;
+    }
+
+    // Called when the sostenuto pedal goes down.
+    pub(crate) fn catch_sostenuto(&mut self) {
+        self.sostenuto = self.is_playing();
+    }
+
+    // Changes the key without restarting the envelopes, for legato playing in mono mode.
+    // The pitch slides from where it is if the portamento time is given.
+    pub(crate) fn change_key(&mut self, key: i32, portamento_time: Option<f32>) {
+        let pitch = self.key as f32 + self.portamento.get_offset();
+        self.key = key;
+        match portamento_time {
+            Some(time) => self.portamento.start(pitch - key as f32, time),
+            None => self.portamento.stop(),
+        }
+        self.voice_state = VoiceState::Playing;
+    }
+
+    pub(crate) fn is_one_shot(&self) -> bool {
+        self.one_shot
+    }
+
+    // Returns `true` if the note is sounding and has not been released.
+    pub(crate) fn is_playing(&self) -> bool {
+        self.note_gain >= NON_AUDIBLE && !self.ended && self.voice_state == VoiceState::Playing
+    }
+
+    // Returns `true` if the note is sounding and has not started its release,
+    // which includes a released note sustained by the pedals.
+    pub(crate) fn is_sounding(&self) -> bool {
+        self.note_gain >= NON_AUDIBLE && !self.ended && self.voice_state != VoiceState::Released
+    }
+
+    // Slides the pitch from the given key to the key of the voice.
+    pub(crate) fn start_portamento(&mut self, from_key: i32, time: f32) {
+        self.portamento.start((from_key - self.key) as f32, time);
+    }
+
     pub(crate) fn end(&mut self) {
         if self.voice_state == VoiceState::Playing {
             self.voice_state = VoiceState::ReleaseRequested;
@@ -170,6 +275,7 @@ impl Voice {
 
         let (vol_env_output, vol_env_on) = self.vol_env.render();
         if !vol_env_on {
+            self.ended = true;
             return None;
         }
 
@@ -177,35 +283,83 @@ impl Voice {
         let vib_lfo_output = self.vib_lfo.render();
         let mod_lfo_output = self.mod_lfo.render();
 
-        let vib_pitch_change =
-            (0.01 * channel_info.get_modulation() + self.vib_lfo_to_pitch) * vib_lfo_output;
+        let pressure = channel_info.get_pressure(self.key);
+        let pressure_routing = channel_info.get_pressure_routing();
+
+        let vibrato_depth =
+            channel_info.get_modulation() + pressure_routing.vibrato_depth * pressure;
+        let vib_pitch_change = (0.01 * vibrato_depth + self.vib_lfo_to_pitch) * vib_lfo_output;
         let mod_pitch_change =
             self.mod_lfo_to_pitch * mod_lfo_output + self.mod_env_to_pitch * mod_env_output;
-        let channel_pitch_change = channel_info.get_tune() + channel_info.get_pitch_bend();
-        let pitch = self.key as f32 + vib_pitch_change + mod_pitch_change + channel_pitch_change;
-
-        let osc_output = self.oscillator.render(pitch);
-        osc_output?;
-        let osc_output = osc_output.unwrap();
+        // The pitch of drums does not follow the pitch bend and the tuning.
+        let channel_pitch_change = if channel_info.is_percussion() {
+            0.0
+        } else {
+            channel_info.get_tune() + channel_info.get_pitch_bend()
+        };
+        let pitch = self.key as f32
+            + self.portamento.render()
+            + vib_pitch_change
+            + mod_pitch_change
+            + channel_pitch_change;
+
+        let osc_output = match self.generator.get_mut() {
+            Some(generator) => generator.render(pitch + self.tune),
+            None => self.oscillator.render(pitch),
+        };
+        let Some(osc_output) = osc_output else {
+            self.ended = true;
+            return None;
+        };
+
+        // The sound controllers and the pressure also affect the notes already playing.
+        let cutoff_change = self.get_cutoff_change(channel_info);
+        let harmonic_content = channel_info.get_harmonic_content();
+        if cutoff_change != self.cutoff_change || harmonic_content != self.harmonic_content {
+            self.cutoff_change = cutoff_change;
+            self.harmonic_content = harmonic_content;
+            self.resonance = Voice::get_resonance(harmonic_content);
+            self.filter_changed = true;
+        }
 
-        if self.dynamic_cutoff {
+        if self.dynamic_cutoff || self.filter_changed {
             let cents = self.mod_lfo_to_cutoff as f32 * mod_lfo_output
-                + self.mod_env_to_cutoff as f32 * mod_env_output;
+                + self.mod_env_to_cutoff as f32 * mod_env_output
+                + self.cutoff_change;
             let factor = cents_to_multiplying_factor(cents);
             let new_cutoff = factor * self.cutoff;
 
-            // The cutoff change is limited within x0.5 and x2 to reduce pop noise.
-            let lower_limit = 0.5 * self.smoothed_cutoff;
-            let upper_limit = 2.0 * self.smoothed_cutoff;
-            self.smoothed_cutoff = new_cutoff.clamp(lower_limit, upper_limit);
-
-            self.filter
-                .set_low_pass_filter(self.smoothed_cutoff, self.resonance);
+            if self.filter_type == FilterType::LowPass {
+                // The cutoff change is limited within x0.5 and x2 to reduce pop noise.
+                // The SoundFont filter keeps this limit so that SoundFonts sound as they did,
+                // since the limit also slows down fast sweeps of the modulation envelope.
+                // Sources which need fast and clean sweeps use StateVariableLowPass instead.
+                let lower_limit = 0.5 * self.smoothed_cutoff;
+                let upper_limit = 2.0 * self.smoothed_cutoff;
+                self.smoothed_cutoff = new_cutoff.clamp(lower_limit, upper_limit);
+
+                self.filter
+                    .set_low_pass_filter(self.smoothed_cutoff, self.resonance);
+
+                // A large change takes a few samples to reach the target.
+                self.filter_changed = self.smoothed_cutoff != new_cutoff;
+            } else {
+                // The state variable filter needs no limit.
+                self.sv_filter
+                    .set_filter(self.filter_type, new_cutoff, self.resonance);
+
+                self.filter_changed = false;
+            }
         }
-        let output = self.filter.render(osc_output);
+        let output = if self.filter_type == FilterType::LowPass {
+            self.filter.render(osc_output)
+        } else {
+            self.sv_filter.render(osc_output)
+        };
 
         self.previous_mix_gain_left = self.current_mix_gain_left;
         self.previous_mix_gain_right = self.current_mix_gain_right;
+        self.previous_mix_gain = self.current_mix_gain;
         self.previous_reverb_send = self.current_reverb_send;
         self.previous_chorus_send = self.current_chorus_send;
 
@@ -218,19 +372,15 @@ impl Voice {
             let decibels = self.mod_lfo_to_volume * mod_lfo_output;
             mix_gain *= decibels_to_linear(decibels);
         }
-
-        let angle = (consts::PI / 200.0) * (channel_info.get_pan() + self.instrument_pan + 50.0);
-        if angle <= 0.0 {
-            self.current_mix_gain_left = mix_gain;
-            self.current_mix_gain_right = 0.0;
-        } else if angle >= HALF_PI {
-            self.current_mix_gain_left = 0.0;
-            self.current_mix_gain_right = mix_gain;
-        } else {
-            self.current_mix_gain_left = mix_gain * angle.cos();
-            self.current_mix_gain_right = mix_gain * angle.sin();
+        if pressure > 0.0 && pressure_routing.volume != 0.0 {
+            mix_gain *= decibels_to_linear(pressure_routing.volume * pressure);
         }
 
+        let (pan_left, pan_right) = get_pan_gains(channel_info.get_pan() + self.instrument_pan);
+        self.current_mix_gain = mix_gain;
+        self.current_mix_gain_left = mix_gain * pan_left;
+        self.current_mix_gain_right = mix_gain * pan_right;
+
         self.current_reverb_send =
             (channel_info.get_reverb_send() + self.instrument_reverb).clamp(0.0, 1.0);
         self.current_chorus_send =
@@ -239,6 +389,7 @@ impl Voice {
         if self.voice_length == 0 {
             self.previous_mix_gain_left = self.current_mix_gain_left;
             self.previous_mix_gain_right = self.current_mix_gain_right;
+            self.previous_mix_gain = self.current_mix_gain;
             self.previous_reverb_send = self.current_reverb_send;
             self.previous_chorus_send = self.current_chorus_send;
         }
@@ -254,15 +405,35 @@ impl Voice {
             return;
         }
 
-        if self.voice_state == VoiceState::ReleaseRequested && !channel_info.get_hold_pedal() {
+        let sustained =
+            channel_info.get_hold_pedal() || (self.sostenuto && channel_info.get_sostenuto_pedal());
+        if self.voice_state == VoiceState::ReleaseRequested && !sustained {
             self.vol_env.release();
             self.mod_env.release();
             self.oscillator.release();
+            if let Some(generator) = self.generator.get_mut() {
+                generator.release();
+            }
 
             self.voice_state = VoiceState::Released;
         }
     }
 
+    // The change of the filter cutoff in cents by the channel.
+    fn get_cutoff_change(&self, channel_info: &Channel) -> f32 {
+        let pressure = channel_info.get_pressure(self.key);
+        channel_info.get_brightness() + channel_info.get_pressure_routing().cutoff * pressure
+    }
+
+    // The resonance is a linear gain of the peak, which cannot be lower than no peak.
+    fn get_resonance(decibels: f32) -> f32 {
+        if decibels > 0.0 {
+            decibels_to_linear(decibels)
+        } else {
+            1.0
+        }
+    }
+
     pub(crate) fn get_priority(&self) -> u8 {
         if self.note_gain < NON_AUDIBLE {
             0
@@ -271,3 +442,15 @@ impl Voice {
         }
     }
 }
+
+/// Gets the gains of the left and right outputs for the pan, from -50 (left) to 50 (right).
+pub(crate) fn get_pan_gains(pan: f32) -> (f32, f32) {
+    let angle = (consts::PI / 200.0) * (pan + 50.0);
+    if angle <= 0.0 {
+        (1.0, 0.0)
+    } else if angle >= HALF_PI {
+        (0.0, 1.0)
+    } else {
+        (angle.cos(), angle.sin())
+    }
+}
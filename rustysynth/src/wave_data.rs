@@ -0,0 +1,124 @@
+use std::borrow::Cow;
+use std::ops::Range;
+use std::sync::Arc;
+
+use crate::oscillator::View;
+
+/// The sample data shared by the sounds of a sound source.
+/// The data is either decoded in memory, or kept as 16-bit little-endian bytes
+/// in the buffer it was loaded from.
+#[derive(Debug, Clone)]
+pub enum WaveData {
+    /// The decoded samples.
+    Samples(Arc<[i16]>),
+    /// The samples stored as bytes in a shared buffer.
+    Bytes {
+        data: Arc<[u8]>,
+        /// The position of the first sample in bytes.
+        offset: usize,
+        /// The number of samples.
+        len: usize,
+    },
+    /// The samples stored as bytes in a static buffer, such as one embedded by `include_bytes!`.
+    Static(&'static [u8]),
+}
+
+impl WaveData {
+    /// Gets the number of samples.
+    pub fn len(&self) -> usize {
+        match self {
+            WaveData::Samples(data) => data.len(),
+            WaveData::Bytes { len, .. } => *len,
+            WaveData::Static(data) => data.len() / 2,
+        }
+    }
+
+    /// Returns `true` if there are no samples.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Gets the samples in the range.
+    /// The samples are borrowed if they are already decoded, and decoded otherwise.
+    /// Panics if the range is out of bounds.
+    pub fn get_samples(&self, range: Range<usize>) -> Cow<'_, [i16]> {
+        match self {
+            WaveData::Samples(data) => Cow::Borrowed(&data[range]),
+            WaveData::Bytes { data, offset, len } => {
+                assert!(range.end <= *len, "the sample range is out of bounds");
+                Cow::Owned(decode(
+                    &data[offset + 2 * range.start..offset + 2 * range.end],
+                ))
+            }
+            WaveData::Static(data) => Cow::Owned(decode(&data[2 * range.start..2 * range.end])),
+        }
+    }
+}
+
+/// A sample in `WaveData`, which is given by `Sound::get_wave_view`.
+#[derive(Debug, Clone, Default)]
+pub struct WaveView {
+    pub data: WaveData,
+    pub start: usize,
+    pub end: usize,
+}
+
+#[allow(clippy::len_without_is_empty)]
+impl WaveView {
+    pub fn len(&self) -> usize {
+        self.end - self.start
+    }
+
+    /// Gets the sample as decoded samples.
+    /// The samples are shared if they are already decoded, and copied otherwise.
+    /// Panics if the sample is out of the bounds of the data.
+    pub fn to_view(&self) -> View<i16> {
+        match &self.data {
+            WaveData::Samples(data) => View {
+                data: data.clone(),
+                start: self.start,
+                end: self.end,
+            },
+            data => View {
+                data: Arc::from(data.get_samples(self.start..self.end).into_owned()),
+                start: 0,
+                end: self.len(),
+            },
+        }
+    }
+}
+
+impl From<View<i16>> for WaveView {
+    fn from(view: View<i16>) -> Self {
+        WaveView {
+            data: WaveData::Samples(view.data),
+            start: view.start,
+            end: view.end,
+        }
+    }
+}
+
+impl Default for WaveData {
+    fn default() -> Self {
+        WaveData::Samples(Arc::from([]))
+    }
+}
+
+impl From<Vec<i16>> for WaveData {
+    fn from(data: Vec<i16>) -> Self {
+        WaveData::Samples(Arc::from(data))
+    }
+}
+
+impl From<Arc<[i16]>> for WaveData {
+    fn from(data: Arc<[i16]>) -> Self {
+        WaveData::Samples(data)
+    }
+}
+
+fn decode(bytes: &[u8]) -> Vec<i16> {
+    bytes
+        .chunks_exact(2)
+        .map(|pair| i16::from_le_bytes([pair[0], pair[1]]))
+        .collect()
+}